@@ -0,0 +1,64 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::fmt::Write;
+
+/// Generates a journal with a transaction for every day of `years` years, using most of the syntax.
+fn generate_journal(years: i32) -> String {
+    let mut journal = indoc::indoc! {r#"
+        set title "Household"
+        set default_commodity JPY
+        set default_timezone Asia/Tokyo
+
+        let rent = 85000
+
+        auto expense/food
+          ---
+          shop: online
+          ---
+          tax/consumption ; JPY ;  10%
+          expense/food    ;     ; -10%
+
+        budget monthly expense/food ; JPY ; 60000
+
+    "#}
+    .to_string();
+
+    let shops = ["スーパー", "Bakery", "\"Corner shop\"", "Café"];
+    for year in 0..years {
+        for day in 0..365 {
+            let date = chrono::NaiveDate::from_ymd_opt(2016 + year, 1, 1).unwrap()
+                + chrono::Days::new(day);
+            let shop = shops[day as usize % shops.len()];
+            let amount = 1000 + (day * 37) % 5000;
+            writeln!(journal, "{date} * Groceries at {shop} // receipt {day}").unwrap();
+            if day % 7 == 0 {
+                journal.push_str("  ---\n  shop: online\n  tags: [food, weekly]\n  ---\n");
+            }
+            writeln!(journal, "  expense/food      ; JPY ; {amount}").unwrap();
+            writeln!(journal, "  assets/\"my bank\" ;     ; -({amount} * 2) / 2").unwrap();
+            if day % 30 == 0 {
+                writeln!(journal, "\n{date} 12:30:00+09:00 Rent").unwrap();
+                journal.push_str("  expense/rent ; JPY ; rent\n  資産/現金     ; JPY ;\n");
+            }
+            journal.push('\n');
+        }
+    }
+
+    journal
+}
+
+fn bench_lex(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lex");
+    for years in [1, 10] {
+        let journal = generate_journal(years);
+        group.throughput(Throughput::Bytes(journal.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("journal", years),
+            &journal,
+            |b, journal| b.iter(|| boki::lex::lex_string(journal).expect("Failed to lex.")),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_lex);
+criterion_main!(benches);
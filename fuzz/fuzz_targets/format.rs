@@ -0,0 +1,41 @@
+//! Formats arbitrary input, and checks that formatting keeps all tokens and is idempotent.
+#![no_main]
+
+use boki::{lex, tokens};
+use libfuzzer_sys::fuzz_target;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[allow(dead_code)]
+#[path = "../../src/bin/error.rs"]
+mod error;
+
+#[allow(dead_code)]
+#[path = "../../src/bin/cli"]
+mod cli {
+    pub mod format;
+}
+
+fn significant_tokens(content: &str) -> Vec<tokens::Token> {
+    lex::lex_string(content)
+        .expect("Formatted content should be lexable.")
+        .iter()
+        .map(|t| t.token().clone())
+        .filter(|t| !matches!(t, tokens::Token::Whitespace))
+        .collect()
+}
+
+fn format(content: &str) -> Option<String> {
+    let filename = Arc::new(PathBuf::from("<fuzz>"));
+    cli::format::format_content(filename, content.into(), &Default::default(), false).ok()
+}
+
+fuzz_target!(|content: &str| {
+    let Some(once) = format(content) else {
+        return;
+    };
+    let twice = format(&once).expect("Formatted content should be formattable.");
+
+    assert_eq!(significant_tokens(&once), significant_tokens(content));
+    assert_eq!(once, twice);
+});
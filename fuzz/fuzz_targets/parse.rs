@@ -0,0 +1,21 @@
+//! Lexes, folds and parses arbitrary input the same way as `boki export`.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code)]
+#[path = "../../src/bin/error.rs"]
+mod error;
+
+#[allow(dead_code)]
+#[path = "../../src/bin/cli"]
+mod cli {
+    pub mod export;
+}
+
+fuzz_target!(|content: &str| {
+    let Ok(tokens) = boki::lex::lex_string(content) else {
+        return;
+    };
+    let _ = cli::export::parse::parse_tokens(tokens.into());
+});
@@ -1,28 +1,185 @@
-use chrono::{DateTime, FixedOffset};
+pub type Timestamp = crate::tokens::Timestamp;
+pub type TransactionStatus = crate::tokens::TransactionStatus;
+pub type Operator = crate::tokens::Operator;
 
-pub type Timestamp = DateTime<FixedOffset>;
+/// The version of the serialized AST, which e.g. cached parse results are keyed on.
+///
+/// This must be incremented whenever the serialized form of any node changes, including the types
+/// it shares with [crate::tokens].
+pub const FORMAT_VERSION: u32 = 1;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct TransactionHeader {
     pub timestamp: Timestamp,
+    pub status: Option<TransactionStatus>,
+    pub description: Option<String>,
     pub attributes: serde_yaml::Mapping,
 }
 
-#[derive(Clone, Debug)]
+impl TransactionHeader {
+    /// A header with only a timestamp, i.e. without status, description or attributes.
+    pub fn new(timestamp: Timestamp) -> Self {
+        Self {
+            timestamp,
+            status: None,
+            description: None,
+            attributes: Default::default(),
+        }
+    }
+}
+
+/// An arithmetic expression in the amount column of a posting, e.g. `(1200 * 3) + 450`.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Expression {
+    Amount(i64),
+    /// A reference to a constant defined with `let`.
+    Variable(String),
+    /// An expression in parentheses, kept so that it can be written back as it was.
+    Parenthesized(Box<Expression>),
+    Negation(Box<Expression>),
+    Binary(Box<Expression>, Operator, Box<Expression>),
+    /// A rounding function, e.g. `round(rent / 3)`. Only the divisions inside one may have a remainder.
+    Rounded(Rounding, Box<Expression>),
+}
+
+impl From<i64> for Expression {
+    fn from(value: i64) -> Self {
+        Expression::Amount(value)
+    }
+}
+
+impl std::fmt::Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expression::Amount(x) => write!(f, "{x}"),
+            Expression::Variable(x) => write!(f, "{x}"),
+            Expression::Parenthesized(x) => write!(f, "({x})"),
+            Expression::Negation(x) => write!(f, "-{x}"),
+            Expression::Binary(l, op, r) => write!(f, "{l} {op} {r}"),
+            Expression::Rounded(rounding, x) => write!(f, "{rounding}({x})"),
+        }
+    }
+}
+
+/// How the divisions inside a rounding function are rounded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Rounding {
+    /// Half away from zero, e.g. `round(5 / 2)` is 3 and `round(-5 / 2)` is -3.
+    Round,
+    /// Towards negative infinity, e.g. `floor(-5 / 2)` is -3.
+    Floor,
+    /// Towards positive infinity, e.g. `ceil(5 / 2)` is 3.
+    Ceil,
+}
+
+impl Rounding {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "round" => Some(Rounding::Round),
+            "floor" => Some(Rounding::Floor),
+            "ceil" => Some(Rounding::Ceil),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Rounding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rounding::Round => write!(f, "round"),
+            Rounding::Floor => write!(f, "floor"),
+            Rounding::Ceil => write!(f, "ceil"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
 pub struct Posting {
     pub account: String,
     pub commodity: Option<String>,
-    pub amount: Option<i64>,
+    pub amount: Option<Expression>,
+    pub attributes: serde_yaml::Mapping,
+    /// An optional date on which the posting took effect, e.g. a settlement date.
+    pub date: Option<Timestamp>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct Transaction {
     pub header: TransactionHeader,
     pub postings: Vec<Posting>,
 }
 
-#[derive(Clone, Debug)]
+/// The amount of a posting generated by an [AutoRule].
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub enum AutoAmount {
+    Fixed(Expression),
+    /// A share of the matched posting's amount, in basis points.
+    Percentage(i64),
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct AutoPosting {
+    pub account: String,
+    /// The commodity of the generated posting. Defaults to the one of the matched posting.
+    pub commodity: Option<String>,
+    pub amount: AutoAmount,
+}
+
+/// A rule which adds postings to every transaction with a posting to `account` (or one of its subaccounts).
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct AutoRule {
+    /// Without an account, the rule adds its postings once to every transaction with the attributes.
+    pub account: Option<String>,
+    /// Only transactions which have all of these attributes are matched.
+    pub attributes: serde_yaml::Mapping,
+    pub postings: Vec<AutoPosting>,
+}
+
+/// A spending limit for an account, e.g. `budget monthly expense/food ; JPY ; 60000`.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Budget {
+    pub period: String,
+    pub account: String,
+    pub commodity: Option<String>,
+    pub amount: Expression,
+}
+
+/// Declares the type of an account and its subaccounts, e.g. `account cost ; expense`.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct AccountDeclaration {
+    pub account: String,
+    pub account_type: String,
+}
+
+/// The value of a `set` directive, before it is checked against the type of the setting.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum Value {
+    /// A bare value, e.g. `JPY`, `true` or `Asia/Tokyo`.
+    Identifier(String),
+    String(String),
+    Integer(i64),
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Identifier(x) => write!(f, "{x}"),
+            Value::String(x) => write!(f, "{x:?}"),
+            Value::Integer(x) => write!(f, "{x}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub enum ASTNode {
     Transaction(Transaction),
-    SetAttribute(String, String),
+    SetAttribute(String, Value),
+    AutoRule(AutoRule),
+    Budget(Budget),
+    AccountDeclaration(AccountDeclaration),
+    /// Starts a scope, whose attributes are merged into every transaction until the matching [ASTNode::End].
+    With(serde_yaml::Mapping),
+    End,
+    /// Defines a named constant, e.g. `let rent = 85000`, which can be used in any following amount.
+    Let(String, Expression),
 }
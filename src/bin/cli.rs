@@ -1,2 +1,12 @@
+pub mod balance_sheet;
+pub mod budget;
+pub mod check;
+pub mod close;
 pub mod export;
 pub mod format;
+pub mod income_statement;
+mod report;
+pub mod schema;
+#[cfg(test)]
+mod testing;
+pub mod watch;
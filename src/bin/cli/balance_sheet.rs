@@ -0,0 +1,119 @@
+//! The `boki bs` report, which shows the balances of the assets, liabilities and equity at the end of each period.
+
+use boki::output::{self, AccountType};
+
+use super::export::{self, Result};
+use super::report::{self, Period, Table};
+
+/// The row in the equity section which holds the income and expenses which have not been closed yet.
+const NET_INCOME_ROW: &str = "(net income)";
+
+fn render(journal: &output::Journal, period: Period) -> Result<Table> {
+    let starts = report::periods(journal, period);
+    let mut table = report::period_table(period, &starts);
+
+    let assets = report::compute_balances(journal, &starts, &[AccountType::Assets], true)?;
+    let liabilities =
+        report::compute_balances(journal, &starts, &[AccountType::Liabilities], true)?;
+    let mut equity = report::compute_balances(journal, &starts, &[AccountType::Equity], true)?;
+
+    let net_income = report::compute_balances(
+        journal,
+        &starts,
+        &[AccountType::Income, AccountType::Expense],
+        true,
+    )?;
+    for ((_, commodity), amounts) in net_income {
+        let row = equity
+            .entry((NET_INCOME_ROW.to_string(), commodity.clone()))
+            .or_insert_with(|| vec![0; starts.len()]);
+        for (x, y) in row.iter_mut().zip(amounts) {
+            *x = x.checked_add(y).ok_or_else(|| {
+                export::error::out_of_range(format!("the net income in {commodity}"))
+            })?;
+        }
+    }
+
+    report::add_section(&mut table, "assets", &assets, 1)?;
+    report::add_section(&mut table, "liabilities", &liabilities, -1)?;
+    report::add_section(&mut table, "equity", &equity, -1)?;
+
+    Ok(table)
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    pub journal: export::JournalArgs,
+
+    #[arg(short, long, value_enum, default_value_t = Period::Monthly)]
+    period: Period,
+}
+
+pub fn run(args: &Args) -> Result<()> {
+    let journal = args.journal.compile()?;
+    print!("{}", render(&journal, args.period)?);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cli::testing::transaction;
+
+    #[test]
+    fn test_render() {
+        let journal = output::Journal {
+            transactions: vec![
+                transaction(
+                    "2026-01-01T00:00:00+09:00",
+                    &[("assets/bank", 1000000), ("equity/opening", -1000000)],
+                ),
+                transaction(
+                    "2026-02-10T00:00:00+09:00",
+                    &[("expense/food", 40000), ("liabilities/card", -40000)],
+                ),
+                transaction(
+                    "2026-04-25T00:00:00+09:00",
+                    &[("income/salary", -300000), ("assets/bank", 300000)],
+                ),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            render(&journal, Period::Quarterly)
+                .expect("Failed.")
+                .to_string(),
+            [
+                "account             commodity  2026-Q1  2026-Q2",
+                "assets",
+                "  assets/bank       JPY        1000000  1300000",
+                "total assets        JPY        1000000  1300000",
+                "liabilities",
+                "  liabilities/card  JPY          40000    40000",
+                "total liabilities   JPY          40000    40000",
+                "equity",
+                "  (net income)      JPY         -40000   260000",
+                "  equity/opening    JPY        1000000  1000000",
+                "total equity        JPY         960000  1260000",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_render_out_of_range() {
+        let journal = output::Journal {
+            transactions: vec![
+                transaction("2026-01-01T00:00:00+09:00", &[("assets/cash", i64::MAX)]),
+                transaction("2026-01-02T00:00:00+09:00", &[("assets/cash", i64::MAX)]),
+            ],
+            ..Default::default()
+        };
+
+        render(&journal, Period::Monthly).expect_err("Should have failed.");
+    }
+}
@@ -0,0 +1,186 @@
+//! The `boki budget` report, which compares the budgets of a journal against its actual postings.
+
+use boki::{output, utils};
+
+use super::export::{self, Result};
+use super::report::{self, Align, Period, Table};
+
+/// The actual amount posted against a budget in a single period.
+#[derive(Debug, PartialEq)]
+struct BudgetLine<'a> {
+    period: String,
+    budget: &'a output::Budget,
+    actual: i64,
+    remaining: i64,
+}
+
+impl BudgetLine<'_> {
+    /// How much of the budget has been used, e.g. `75.0%`.
+    fn used(&self) -> String {
+        match self.budget.amount {
+            0 => "-".to_string(),
+            x => format!("{:.1}%", self.actual as f64 * 100.0 / x as f64),
+        }
+    }
+}
+
+/// Computes a line for every budget and every period between the first and the last transaction.
+fn compute_budget_lines(journal: &output::Journal) -> Result<Vec<BudgetLine<'_>>> {
+    let mut lines = vec![];
+    for budget in &journal.budgets {
+        let period = Period::from(budget.period);
+        for start in report::periods(journal, period) {
+            let end = period.next(start);
+            let actual = journal
+                .transactions
+                .iter()
+                .filter(|t| (start..end).contains(&report::transaction_date(t)))
+                .flat_map(|t| &t.postings)
+                .filter(|p| {
+                    p.commodity == budget.commodity
+                        && utils::is_same_or_subaccount(&budget.account, &p.account)
+                })
+                .try_fold(0i64, |sum, p| sum.checked_add(p.amount))
+                .ok_or_else(|| {
+                    export::error::out_of_range(format!("the actual amount of {}", budget.account))
+                })?;
+            let remaining = budget.amount.checked_sub(actual).ok_or_else(|| {
+                export::error::out_of_range(format!("the remaining budget of {}", budget.account))
+            })?;
+
+            lines.push(BudgetLine {
+                period: period.name(start),
+                budget,
+                actual,
+                remaining,
+            });
+        }
+    }
+
+    Ok(lines)
+}
+
+fn render(lines: &[BudgetLine]) -> Table {
+    let mut table = Table::new(&[
+        ("period", Align::Left),
+        ("account", Align::Left),
+        ("commodity", Align::Left),
+        ("budget", Align::Right),
+        ("actual", Align::Right),
+        ("used", Align::Right),
+        ("remaining", Align::Right),
+    ]);
+
+    for line in lines {
+        table.push(vec![
+            line.period.clone(),
+            line.budget.account.clone(),
+            line.budget.commodity.clone(),
+            line.budget.amount.to_string(),
+            line.actual.to_string(),
+            line.used(),
+            line.remaining.to_string(),
+        ]);
+    }
+
+    table
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    pub journal: export::JournalArgs,
+}
+
+pub fn run(args: &Args) -> Result<()> {
+    let journal = args.journal.compile()?;
+    print!("{}", render(&compute_budget_lines(&journal)?));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cli::testing;
+
+    fn transaction(timestamp: &str, account: &str, amount: i64) -> output::Transaction {
+        testing::transaction(timestamp, &[(account, amount), ("assets/cash", -amount)])
+    }
+
+    fn journal(period: output::BudgetPeriod) -> output::Journal {
+        output::Journal {
+            transactions: vec![
+                transaction("2026-01-05T00:00:00+09:00", "expense/food/groceries", 20000),
+                transaction("2026-01-20T00:00:00+09:00", "expense/food", 25000),
+                transaction("2026-01-21T00:00:00+09:00", "expense/rent", 80000),
+                transaction("2026-03-01T00:00:00+09:00", "expense/food", 70000),
+            ],
+            budgets: vec![output::Budget {
+                period,
+                account: "expense/food".to_string(),
+                commodity: "JPY".to_string(),
+                amount: 60000,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_monthly() {
+        let journal = journal(output::BudgetPeriod::Monthly);
+        let lines = compute_budget_lines(&journal).expect("Failed.");
+
+        assert_eq!(
+            lines
+                .iter()
+                .map(|x| (x.period.as_str(), x.actual, x.used(), x.remaining))
+                .collect::<Vec<_>>(),
+            vec![
+                ("2026-01", 45000, "75.0%".to_string(), 15000),
+                ("2026-02", 0, "0.0%".to_string(), 60000),
+                ("2026-03", 70000, "116.7%".to_string(), -10000),
+            ]
+        );
+    }
+
+    #[rstest::rstest]
+    #[case::quarterly(output::BudgetPeriod::Quarterly, "2026-Q1")]
+    #[case::yearly(output::BudgetPeriod::Yearly, "2026")]
+    fn test_longer_periods(#[case] period: output::BudgetPeriod, #[case] name: &str) {
+        let journal = journal(period);
+        let lines = compute_budget_lines(&journal).expect("Failed.");
+
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].period, name);
+        assert_eq!(lines[0].actual, 115000);
+    }
+
+    #[test]
+    fn test_empty_journal() {
+        let mut journal = journal(output::BudgetPeriod::Monthly);
+        journal.transactions.clear();
+        assert!(compute_budget_lines(&journal).expect("Failed.").is_empty());
+    }
+
+    #[test]
+    fn test_actual_out_of_range() {
+        let mut journal = journal(output::BudgetPeriod::Monthly);
+        journal.transactions = vec![
+            transaction("2026-01-05T00:00:00+09:00", "expense/food", i64::MAX),
+            transaction("2026-01-06T00:00:00+09:00", "expense/food", 1),
+        ];
+        compute_budget_lines(&journal).expect_err("Should have failed.");
+    }
+
+    #[test]
+    fn test_remaining_out_of_range() {
+        let mut journal = journal(output::BudgetPeriod::Monthly);
+        journal.transactions = vec![transaction(
+            "2026-01-05T00:00:00+09:00",
+            "expense/food",
+            i64::MIN + 1,
+        )];
+        compute_budget_lines(&journal).expect_err("Should have failed.");
+    }
+}
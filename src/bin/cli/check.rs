@@ -0,0 +1,33 @@
+use super::export::{self, Result};
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    pub journal: export::JournalArgs,
+}
+
+pub fn run(args: &Args) -> Result<()> {
+    let journal = args.journal.compile()?;
+    println!(
+        "No errors found in {} transactions.",
+        journal.transactions.len()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_smoke() {
+        let args = Args {
+            journal: export::JournalArgs {
+                files: vec!["docs/examples/01-books-journal/books.boki".into()],
+                no_cache: true,
+            },
+        };
+        run(&args).expect("Failed.");
+    }
+}
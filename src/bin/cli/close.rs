@@ -0,0 +1,318 @@
+//! The `boki close` command, which carries the balances of a year over into the journal of the next one.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use boki::lex;
+use boki::output::{self, AccountType};
+use chrono::NaiveDate;
+
+use super::export::{self, Result};
+use super::{format, report};
+
+/// Balances per account and commodity.
+type Balances = BTreeMap<(String, String), i64>;
+
+/// Adds `amount` to the entry of `key`, failing with a description of the entry if it goes out of range.
+fn add<K: Ord>(
+    balances: &mut BTreeMap<K, i64>,
+    key: K,
+    amount: i64,
+    what: impl FnOnce() -> String,
+) -> Result<()> {
+    let balance = balances.entry(key).or_default();
+    *balance = balance
+        .checked_add(amount)
+        .ok_or_else(|| export::error::out_of_range(what()))?;
+    Ok(())
+}
+
+fn negate(amount: i64, what: impl FnOnce() -> String) -> Result<i64> {
+    amount
+        .checked_neg()
+        .ok_or_else(|| export::error::out_of_range(what()))
+}
+
+/// Returns the first day of the fiscal `year`, which starts in the month set by `fiscal_year_start` (January by default).
+fn fiscal_year_start(journal: &output::Journal, year: i32) -> NaiveDate {
+    let month = journal.header.fiscal_year_start.unwrap_or(1);
+    NaiveDate::from_ymd_opt(year, month, 1).unwrap()
+}
+
+/// Computes the balances of all accounts before `end`.
+fn compute_balances(journal: &output::Journal, end: NaiveDate) -> Result<Balances> {
+    let mut balances = Balances::new();
+
+    for t in journal
+        .transactions
+        .iter()
+        .filter(|t| report::transaction_date(t) < end)
+    {
+        for p in &t.postings {
+            add(
+                &mut balances,
+                (p.account.clone(), p.commodity.clone()),
+                p.amount,
+                || format!("the balance of {} in {}", p.account, p.commodity),
+            )?;
+        }
+    }
+
+    balances.retain(|_, amount| *amount != 0);
+    Ok(balances)
+}
+
+/// Returns the postings which move the balances of all income and expense accounts into `equity`.
+fn closing_postings(
+    journal: &output::Journal,
+    balances: &Balances,
+    equity: &str,
+) -> Result<Balances> {
+    let mut postings = Balances::new();
+    for ((account, commodity), amount) in balances {
+        if !matches!(
+            report::classify(journal, account),
+            Some(AccountType::Income | AccountType::Expense)
+        ) {
+            continue;
+        }
+
+        let what = || format!("the balance of {account} in {commodity}");
+        postings.insert((account.clone(), commodity.clone()), negate(*amount, what)?);
+        add(
+            &mut postings,
+            (equity.to_string(), commodity.clone()),
+            *amount,
+            || format!("the closing balance of {equity} in {commodity}"),
+        )?;
+    }
+
+    postings.retain(|_, amount| *amount != 0);
+    Ok(postings)
+}
+
+/// Returns the postings which open the remaining balances after closing.
+///
+/// If a commodity does not add up to zero (e.g. because of implicit conversions), the difference is posted to `opening`.
+fn opening_postings(balances: &Balances, closing: &Balances, opening: &str) -> Result<Balances> {
+    let mut postings = balances.clone();
+    for ((account, commodity), amount) in closing {
+        add(
+            &mut postings,
+            (account.clone(), commodity.clone()),
+            *amount,
+            || format!("the opening balance of {account} in {commodity}"),
+        )?;
+    }
+
+    let mut differences: BTreeMap<String, i64> = BTreeMap::new();
+    for ((_, commodity), amount) in &postings {
+        add(&mut differences, commodity.clone(), *amount, || {
+            format!("the total opening balance in {commodity}")
+        })?;
+    }
+    for (commodity, amount) in differences {
+        let what = || format!("the opening balance of {opening} in {commodity}");
+        let amount = negate(amount, what)?;
+        add(
+            &mut postings,
+            (opening.to_string(), commodity.clone()),
+            amount,
+            what,
+        )?;
+    }
+
+    postings.retain(|_, amount| *amount != 0);
+    Ok(postings)
+}
+
+/// Writes an account in boki syntax, quoting the segments which are not plain identifiers.
+fn write_account(account: &str) -> String {
+    account
+        .split('/')
+        .map(|segment| match lex::is_identifier(segment) {
+            true => segment.to_string(),
+            false => format!("\"{}\"", segment.replace('\\', "\\\\").replace('"', "\\\"")),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn write_transaction(out: &mut String, date: NaiveDate, description: &str, postings: &Balances) {
+    writeln!(out, "{date} {description}").unwrap();
+    for ((account, commodity), amount) in postings {
+        let account = write_account(account);
+        writeln!(out, "  {account} ; {commodity} ; {amount}").unwrap();
+    }
+}
+
+fn render(journal: &output::Journal, args: &Args) -> Result<String> {
+    let next_year = fiscal_year_start(journal, args.year + 1);
+    let balances = compute_balances(journal, next_year)?;
+    let closing = closing_postings(journal, &balances, &args.equity)?;
+    let opening = opening_postings(&balances, &closing, &args.opening)?;
+
+    let mut out = String::new();
+    if !closing.is_empty() {
+        writeln!(out, "// Append this to the journal of {}:", args.year).unwrap();
+        write_transaction(
+            &mut out,
+            next_year.pred_opt().unwrap(),
+            "Closing balances",
+            &closing,
+        );
+        writeln!(out).unwrap();
+    }
+    if !opening.is_empty() {
+        writeln!(out, "// Start the journal of {} with this:", args.year + 1).unwrap();
+        write_transaction(&mut out, next_year, "Opening balances", &opening);
+    }
+
+    Ok(format::format_generated(&out))
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    journal: export::JournalArgs,
+
+    /// The fiscal year to close.
+    #[arg(long)]
+    year: i32,
+
+    /// The account which the income and expenses are closed into.
+    #[arg(long, default_value = "equity/retained-earnings")]
+    equity: String,
+
+    /// The account which balances the opening transaction, if needed.
+    #[arg(long, default_value = "equity/opening-balances")]
+    opening: String,
+}
+
+pub fn run(args: &Args) -> Result<()> {
+    let journal = args.journal.compile()?;
+    print!("{}", render(&journal, args)?);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cli::testing::transaction;
+
+    fn args(year: i32) -> Args {
+        Args {
+            journal: export::JournalArgs {
+                files: vec![],
+                no_cache: true,
+            },
+            year,
+            equity: "equity/retained-earnings".to_string(),
+            opening: "equity/opening-balances".to_string(),
+        }
+    }
+
+    fn journal() -> output::Journal {
+        output::Journal {
+            transactions: vec![
+                transaction(
+                    "2025-01-01T00:00:00+09:00",
+                    &[
+                        ("assets/bank", 1000000),
+                        ("equity/opening-balances", -1000000),
+                    ],
+                ),
+                transaction(
+                    "2025-06-25T00:00:00+09:00",
+                    &[("income/salary", -300000), ("assets/bank", 300000)],
+                ),
+                transaction(
+                    "2025-12-20T00:00:00+09:00",
+                    &[("expense/food", 40000), ("liabilities/card", -40000)],
+                ),
+                transaction(
+                    "2026-01-05T00:00:00+09:00",
+                    &[("expense/food", 10000), ("assets/bank", -10000)],
+                ),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render(&journal(), &args(2025)).expect("Failed."),
+            [
+                "// Append this to the journal of 2025:",
+                "2025-12-31 Closing balances",
+                "  equity/retained-earnings ; JPY ;  -260000",
+                "  expense/food             ; JPY ;   -40000",
+                "  income/salary            ; JPY ;   300000",
+                "",
+                "// Start the journal of 2026 with this:",
+                "2026-01-01 Opening balances",
+                "  assets/bank              ; JPY ;  1300000",
+                "  equity/opening-balances  ; JPY ; -1000000",
+                "  equity/retained-earnings ; JPY ;  -260000",
+                "  liabilities/card         ; JPY ;   -40000",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[rstest::rstest]
+    #[case::identifiers("資産/現金", "資産/現金")]
+    #[case::spaces("assets/my bank", "assets/\"my bank\"")]
+    #[case::quotes("assets/\"quoted\"", "assets/\"\\\"quoted\\\"\"")]
+    fn test_write_account(#[case] account: &str, #[case] expected: &str) {
+        assert_eq!(write_account(account), expected);
+    }
+
+    #[test]
+    fn test_fiscal_year() {
+        let mut journal = journal();
+        journal.header.fiscal_year_start = Some(4);
+        assert_eq!(
+            render(&journal, &args(2025)).expect("Failed."),
+            [
+                "// Append this to the journal of 2025:",
+                "2026-03-31 Closing balances",
+                "  equity/retained-earnings ; JPY ;  -250000",
+                "  expense/food             ; JPY ;   -50000",
+                "  income/salary            ; JPY ;   300000",
+                "",
+                "// Start the journal of 2026 with this:",
+                "2026-04-01 Opening balances",
+                "  assets/bank              ; JPY ;  1290000",
+                "  equity/opening-balances  ; JPY ; -1000000",
+                "  equity/retained-earnings ; JPY ;  -250000",
+                "  liabilities/card         ; JPY ;   -40000",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_empty_year() {
+        assert_eq!(render(&journal(), &args(2024)).expect("Failed."), "");
+    }
+
+    #[rstest::rstest]
+    #[case::balance(&[("assets/bank", i64::MAX)], &[("assets/bank", 1)])]
+    #[case::unnegatable(&[("income/salary", i64::MIN)], &[])]
+    #[case::equity(&[("income/salary", i64::MAX)], &[("expense/food", 1)])]
+    fn test_out_of_range(#[case] first: &[(&str, i64)], #[case] second: &[(&str, i64)]) {
+        let journal = output::Journal {
+            transactions: vec![
+                transaction("2025-01-01T00:00:00+09:00", first),
+                transaction("2025-01-02T00:00:00+09:00", second),
+            ],
+            ..Default::default()
+        };
+
+        render(&journal, &args(2025)).expect_err("Should have failed.");
+    }
+}
@@ -1,24 +1,40 @@
-use boki::common_errors::{FileLexError, FileParseError};
+use boki::common_errors::{FileCompileError, FileLexError, FileParseError};
 use std::path::PathBuf;
-use std::rc::Rc;
+use std::sync::Arc;
 
-use boki::{compile, lex, output};
+use boki::{ast, compile, lex, output};
 
-mod error;
-mod parse;
+mod cache;
+pub mod error;
+pub mod parse;
 
-type Result<T> = std::result::Result<T, Box<error::Error>>;
+pub type Result<T> = std::result::Result<T, Box<error::Error>>;
 
-fn read_file(filename: Rc<PathBuf>) -> Result<Rc<str>> {
+fn read_file(filename: Arc<PathBuf>) -> Result<Arc<str>> {
     std::fs::read_to_string(filename.as_ref())
         .map_err(error::map_io_error(filename.clone()))
         .map(|x| x.into())
 }
 
-fn compile_file(filename: Rc<PathBuf>) -> Result<output::Journal> {
+/// A file which was lexed and parsed, but not compiled yet.
+struct ParsedFile {
+    filename: Arc<PathBuf>,
+    content: Arc<str>,
+    /// Every node along with its location in `content`.
+    nodes: Vec<(usize, ast::ASTNode)>,
+}
+
+fn parse_file(filename: Arc<PathBuf>, cache: Option<&cache::Cache>) -> Result<ParsedFile> {
     let content = read_file(filename.clone())?;
+    if let Some(nodes) = cache.and_then(|c| c.get(&filename, &content)) {
+        return Ok(ParsedFile {
+            filename,
+            content,
+            nodes,
+        });
+    }
 
-    let decorated_tokens: Rc<[lex::DecoratedToken]> = lex::lex_string(content.as_ref())
+    let decorated_tokens: Arc<[lex::DecoratedToken]> = lex::lex_string(content.as_ref())
         .map_err(FileLexError::map_from_lexer_error(
             filename.clone(),
             content.clone(),
@@ -31,31 +47,134 @@ fn compile_file(filename: Rc<PathBuf>) -> Result<output::Journal> {
             decorated_tokens.clone(),
         ),
     )?;
+    let nodes: Vec<_> = nodes
+        .into_iter()
+        .map(|(i, node)| (decorated_tokens[i].location(), node))
+        .collect();
 
-    let mut journal = output::Journal::default();
-    for node in nodes {
-        compile::compile_node(&node, &mut journal).map_err(error::map_compile_error())?;
+    if let Some(cache) = cache {
+        cache.put(&filename, &content, &nodes);
     }
 
-    Ok(journal)
+    Ok(ParsedFile {
+        filename,
+        content,
+        nodes,
+    })
+}
+
+/// Applies `f` to every item on a few worker threads, and returns the results in the order of the items.
+fn parallel_map<T, U, F>(items: &[T], f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync,
+{
+    let n_workers = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(items.len());
+    let next = std::sync::atomic::AtomicUsize::new(0);
+
+    let mut results: Vec<(usize, U)> = std::thread::scope(|s| {
+        let workers: Vec<_> = (0..n_workers)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut results = vec![];
+                    loop {
+                        let i = next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        let Some(item) = items.get(i) else {
+                            return results;
+                        };
+                        results.push((i, f(item)));
+                    }
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().expect("A worker thread panicked."))
+            .collect()
+    });
+
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, x)| x).collect()
+}
+
+/// Compiles the files into a single journal, in the given order. Constants and `with` scopes are
+/// local to each file, everything else carries over to the following files.
+///
+/// The files are independent until they are compiled, so they are read, lexed and parsed in parallel.
+/// If several files have errors, the one of the first file is reported. Unless `cache` is `None`,
+/// files which were parsed before are taken from it.
+fn compile_files(filenames: &[PathBuf], cache: Option<&cache::Cache>) -> Result<output::Journal> {
+    let parsed_files = parallel_map(filenames, |filename| {
+        parse_file(Arc::new(filename.clone()), cache)
+    });
+
+    let mut ctx = compile::CompilationContext::default();
+    for file in parsed_files {
+        let file = file?;
+        for (location, node) in file.nodes.iter() {
+            compile::compile_node(node, &mut ctx).map_err(
+                FileCompileError::map_from_compilation_error(
+                    file.filename.clone(),
+                    file.content.clone(),
+                    *location,
+                ),
+            )?;
+        }
+
+        // Errors which are only detected at the end of a file are reported there.
+        ctx.finish_file()
+            .map_err(FileCompileError::map_from_compilation_error(
+                file.filename.clone(),
+                file.content.clone(),
+                file.content.len(),
+            ))?;
+    }
+
+    Ok(ctx.journal)
+}
+
+// The arguments of every command which compiles a journal. This is not a doc comment, because
+// clap would show it as the description of those commands.
+#[derive(clap::Args)]
+pub struct JournalArgs {
+    /// The journal files, which are compiled in the given order.
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+
+    /// Do not read or write the cache of parsed files in `.boki-cache`.
+    #[arg(long)]
+    pub no_cache: bool,
+}
+
+impl JournalArgs {
+    pub fn compile(&self) -> Result<output::Journal> {
+        let cache = (!self.no_cache).then(|| cache::Cache::new(cache::DEFAULT_DIRECTORY.into()));
+        compile_files(&self.files, cache.as_ref())
+    }
 }
 
 #[derive(clap::Args)]
 pub struct Args {
-    file: PathBuf,
+    #[command(flatten)]
+    pub journal: JournalArgs,
 
     #[arg(short, long)]
     output: Option<PathBuf>,
 }
 
 pub fn run(args: &Args) -> Result<()> {
-    let filename = Rc::new(args.file.clone());
-    let journal = compile_file(filename.clone())?;
+    let journal = args.journal.compile()?;
     let output_str = serde_json::to_string(&journal).map_err(error::map_serde_error())?;
 
     match &args.output {
         None => println!("{output_str}"),
-        Some(x) => std::fs::write(x, output_str).expect("Failed to write output file."),
+        Some(x) => {
+            std::fs::write(x, output_str).map_err(error::map_io_error(Arc::new(x.clone())))?
+        }
     };
 
     Ok(())
@@ -64,11 +183,194 @@ pub fn run(args: &Args) -> Result<()> {
 #[cfg(test)]
 mod test {
     use std::path::PathBuf;
-    use std::rc::Rc;
 
     #[test]
     fn test_smoke() {
-        let filename = Rc::new(PathBuf::from("docs/examples/01-books-journal/books.boki"));
-        super::compile_file(filename.clone()).expect("Failed.");
+        let filename = PathBuf::from("docs/examples/01-books-journal/books.boki");
+        super::compile_files(&[filename], None).expect("Failed.");
+    }
+
+    #[test]
+    fn test_export_matches_golden_file() {
+        let filename = PathBuf::from("docs/examples/01-books-journal/books.boki");
+        let journal = super::compile_files(&[filename], None).expect("Failed.");
+        let actual = serde_json::to_value(&journal).expect("Failed.");
+
+        let expected_str = std::fs::read_to_string("docs/examples/01-books-journal/books.json")
+            .expect("Could not read golden file.");
+        let expected: serde_json::Value = serde_json::from_str(&expected_str).expect("Failed.");
+
+        assert_eq!(
+            actual, expected,
+            "The exported JSON changed. If this is intentional, regenerate the golden file and bump `output::SCHEMA_VERSION` if the change is not backwards-compatible."
+        );
+    }
+
+    #[test]
+    fn test_compile_error_is_located() {
+        let filename = std::env::temp_dir().join("boki-test-compile-error.boki");
+        let content = "set default_commodity JPY\n\nset default_comodity JPY\n";
+        std::fs::write(&filename, content).expect("Failed.");
+
+        let err = super::compile_files(&[filename], None).expect_err("Should have failed.");
+        let super::error::Error::Compiler(e) = *err else {
+            panic!("Should have been a compile error.");
+        };
+        assert_eq!(
+            boki::utils::get_position_in_content(content, e.location),
+            (2, 0)
+        );
+    }
+
+    /// Writes each of `contents` to its own temporary file.
+    fn write_files(name: &str, contents: &[&str]) -> Vec<PathBuf> {
+        contents
+            .iter()
+            .enumerate()
+            .map(|(i, content)| {
+                let filename = std::env::temp_dir().join(format!("boki-test-{name}-{i}.boki"));
+                std::fs::write(&filename, content).expect("Failed.");
+                filename
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compiles_files_in_order() {
+        let contents = [
+            "set default_commodity JPY\n",
+            "2026-01-01\n  assets/bank ;; 1000\n  income/salary ;;\n",
+            "2026-01-02\n  expense/food ;; 300\n  assets/bank ;;\n",
+        ];
+        let filenames = write_files("in-order", &contents);
+        let journal = super::compile_files(&filenames, None).expect("Failed.");
+
+        let concatenated = write_files("concatenated", &[&contents.concat()]);
+        let expected = super::compile_files(&concatenated, None).expect("Failed.");
+        assert_eq!(
+            serde_json::to_value(&journal).expect("Failed."),
+            serde_json::to_value(&expected).expect("Failed.")
+        );
+    }
+
+    #[test]
+    fn test_reports_error_of_first_file() {
+        let filenames = write_files(
+            "first-error",
+            &["set default_commodity JPY\n", "set foo bar\n", "@@@\n"],
+        );
+        let err = super::compile_files(&filenames, None).expect_err("Should have failed.");
+        let super::error::Error::Compiler(e) = *err else {
+            panic!("Should have been a compile error.");
+        };
+        assert_eq!(e.filename.as_ref(), &filenames[1]);
+    }
+
+    #[test]
+    fn test_constants_are_local_to_files() {
+        let filenames = write_files(
+            "local-constants",
+            &[
+                "set default_commodity JPY\nlet rent = 85000\n\n2026-01-25\n  expense/rent ;; rent\n  assets/bank ;;\n",
+                "let rent = 90000\n\n2026-02-25\n  expense/rent ;; rent\n  assets/bank ;;\n",
+            ],
+        );
+        let journal = super::compile_files(&filenames, None).expect("Failed.");
+        assert_eq!(
+            journal
+                .transactions
+                .iter()
+                .map(|t| t.postings[0].amount)
+                .collect::<Vec<_>>(),
+            vec![85000, 90000]
+        );
+
+        let filenames = write_files(
+            "leaked-constant",
+            &[
+                "set default_commodity JPY\nlet rent = 85000\n",
+                "2026-01-25\n  expense/rent ;; rent\n  assets/bank ;;\n",
+            ],
+        );
+        let err = super::compile_files(&filenames, None).expect_err("Should have failed.");
+        let super::error::Error::Compiler(e) = *err else {
+            panic!("Should have been a compile error.");
+        };
+        assert_eq!(e.filename.as_ref(), &filenames[1]);
+    }
+
+    #[test]
+    fn test_with_must_be_closed_in_its_file() {
+        let filenames = write_files(
+            "unclosed-with",
+            &["with\n  ---\n  trip: honeymoon\n  ---\n", "end\n"],
+        );
+        let err = super::compile_files(&filenames, None).expect_err("Should have failed.");
+        let super::error::Error::Compiler(e) = *err else {
+            panic!("Should have been a compile error.");
+        };
+        assert_eq!(e.filename.as_ref(), &filenames[0]);
+        assert_eq!(
+            e.location,
+            std::fs::read(&filenames[0]).expect("Failed.").len()
+        );
+    }
+
+    #[test]
+    fn test_output_write_error_is_returned() {
+        let args = super::Args {
+            journal: super::JournalArgs {
+                files: vec!["docs/examples/01-books-journal/books.boki".into()],
+                no_cache: true,
+            },
+            output: Some(std::env::temp_dir().join("boki-test-missing-directory/books.json")),
+        };
+        let err = super::run(&args).expect_err("Should have failed.");
+        assert!(matches!(*err, super::error::Error::IO { .. }));
+    }
+
+    #[test]
+    fn test_parallel_map_keeps_order() {
+        let items: Vec<usize> = (0..100).collect();
+        let doubled = super::parallel_map(&items, |x| x * 2);
+        assert_eq!(doubled, items.iter().map(|x| x * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_cached_files_compile_the_same() {
+        let directory = std::env::temp_dir().join("boki-test-export-cache");
+        let _ = std::fs::remove_dir_all(&directory);
+        let cache = super::cache::Cache::new(directory);
+        let filenames = [PathBuf::from("docs/examples/01-books-journal/books.boki")];
+
+        let expected = super::compile_files(&filenames, None).expect("Failed.");
+        for _ in 0..2 {
+            let journal = super::compile_files(&filenames, Some(&cache)).expect("Failed.");
+            assert_eq!(
+                serde_json::to_value(&journal).expect("Failed."),
+                serde_json::to_value(&expected).expect("Failed.")
+            );
+        }
+    }
+
+    #[test]
+    fn test_cached_compile_error_is_located() {
+        let directory = std::env::temp_dir().join("boki-test-export-cache-error");
+        let _ = std::fs::remove_dir_all(&directory);
+        let cache = super::cache::Cache::new(directory);
+        let content = "set default_commodity JPY\n\nset default_comodity JPY\n";
+        let filenames = write_files("cached-error", &[content]);
+
+        for _ in 0..2 {
+            let err =
+                super::compile_files(&filenames, Some(&cache)).expect_err("Should have failed.");
+            let super::error::Error::Compiler(e) = *err else {
+                panic!("Should have been a compile error.");
+            };
+            assert_eq!(
+                boki::utils::get_position_in_content(content, e.location),
+                (2, 0)
+            );
+        }
     }
 }
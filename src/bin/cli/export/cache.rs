@@ -0,0 +1,187 @@
+//! An on-disk cache of parsed files, so that unchanged files are not lexed and parsed again.
+//!
+//! Every file has a single entry, stored under the hash of its path, along with the hash of its content,
+//! the version of boki which parsed it and the [ast::FORMAT_VERSION] it was parsed into. An entry is only
+//! used if all of them still match, and is overwritten otherwise, so the cache never holds more than one
+//! entry for each file of the journal.
+//! The cache is only an optimization, so any entry which cannot be read or written is skipped.
+use sha2::Digest;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use boki::ast;
+
+pub const DEFAULT_DIRECTORY: &str = ".boki-cache";
+
+/// Any upgrade of boki may change how files are parsed, even if the AST stays the same.
+const BOKI_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Entry {
+    boki_version: String,
+    format_version: u32,
+    content_hash: String,
+    /// Every node along with its location in the content.
+    nodes: Vec<(usize, ast::ASTNode)>,
+}
+
+pub struct Cache {
+    directory: PathBuf,
+}
+
+impl Cache {
+    pub fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+
+    fn path(&self, filename: &Path) -> PathBuf {
+        // The same file can be given as different paths, e.g. `a.boki` and `./a.boki`.
+        let filename = std::fs::canonicalize(filename).unwrap_or_else(|_| filename.to_path_buf());
+        let hash = sha2::Sha256::digest(filename.as_os_str().as_encoded_bytes());
+        self.directory.join(format!("{hash:x}.json"))
+    }
+
+    fn content_hash(content: &str) -> String {
+        format!("{:x}", sha2::Sha256::digest(content.as_bytes()))
+    }
+
+    pub fn get(&self, filename: &Path, content: &str) -> Option<Vec<(usize, ast::ASTNode)>> {
+        let serialized = std::fs::read(self.path(filename)).ok()?;
+        let entry: Entry = serde_json::from_slice(&serialized).ok()?;
+        (entry.boki_version == BOKI_VERSION
+            && entry.format_version == ast::FORMAT_VERSION
+            && entry.content_hash == Self::content_hash(content))
+        .then_some(entry.nodes)
+    }
+
+    pub fn put(&self, filename: &Path, content: &str, nodes: &[(usize, ast::ASTNode)]) {
+        let _ = self.try_put(filename, content, nodes);
+    }
+
+    fn try_put(
+        &self,
+        filename: &Path,
+        content: &str,
+        nodes: &[(usize, ast::ASTNode)],
+    ) -> std::io::Result<()> {
+        if !self.directory.exists() {
+            std::fs::create_dir_all(&self.directory)?;
+            // The cache should never be committed along with the journal.
+            std::fs::write(self.directory.join(".gitignore"), "*\n")?;
+        }
+
+        let entry = Entry {
+            boki_version: BOKI_VERSION.to_string(),
+            format_version: ast::FORMAT_VERSION,
+            content_hash: Self::content_hash(content),
+            nodes: nodes.to_vec(),
+        };
+        let serialized = serde_json::to_vec(&entry)?;
+
+        // Other threads (or processes) may write the same entry, so it is written to a unique file
+        // first and then renamed, which replaces the entry at once.
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = self.path(filename);
+        let temporary = path.with_extension(format!(
+            "{}-{}.tmp",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&temporary, serialized)?;
+        std::fs::rename(&temporary, &path).inspect_err(|_| {
+            let _ = std::fs::remove_file(&temporary);
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cache(name: &str) -> Cache {
+        let directory = std::env::temp_dir().join(format!("boki-test-cache-{name}"));
+        let _ = std::fs::remove_dir_all(&directory);
+        Cache::new(directory)
+    }
+
+    fn nodes() -> Vec<(usize, ast::ASTNode)> {
+        vec![(
+            3,
+            ast::ASTNode::Let("rent".to_string(), ast::Expression::Amount(85000)),
+        )]
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let cache = cache("round-trip");
+        let filename = Path::new("rent.boki");
+        assert!(cache.get(filename, "let rent = 85000\n").is_none());
+
+        cache.put(filename, "let rent = 85000\n", &nodes());
+        let cached = cache
+            .get(filename, "let rent = 85000\n")
+            .expect("Should have been cached.");
+        assert_eq!(format!("{cached:?}"), format!("{:?}", nodes()));
+        assert!(cache.get(filename, "let rent = 90000\n").is_none());
+        assert!(cache
+            .get(Path::new("other.boki"), "let rent = 85000\n")
+            .is_none());
+    }
+
+    #[test]
+    fn test_keeps_one_entry_per_file() {
+        let cache = cache("one-entry");
+        let filename = Path::new("rent.boki");
+        cache.put(filename, "let rent = 85000\n", &nodes());
+        cache.put(filename, "let rent = 90000\n", &nodes());
+
+        let entries = std::fs::read_dir(&cache.directory)
+            .expect("Failed.")
+            .filter(|x| {
+                x.as_ref()
+                    .is_ok_and(|x| x.path().extension() == Some("json".as_ref()))
+            })
+            .count();
+        assert_eq!(entries, 1);
+        assert!(cache.get(filename, "let rent = 85000\n").is_none());
+        assert!(cache.get(filename, "let rent = 90000\n").is_some());
+    }
+
+    #[rstest::rstest]
+    #[case::boki_version(
+        "other-boki-version",
+        format!("\"boki_version\":\"{BOKI_VERSION}\""),
+        "\"boki_version\":\"0.0.0-other\"".to_string()
+    )]
+    #[case::format_version(
+        "other-format-version",
+        format!("\"format_version\":{}", ast::FORMAT_VERSION),
+        format!("\"format_version\":{}", ast::FORMAT_VERSION + 1)
+    )]
+    fn test_ignores_other_versions(
+        #[case] name: &str,
+        #[case] current: String,
+        #[case] other: String,
+    ) {
+        let cache = cache(name);
+        let filename = Path::new("rent.boki");
+        cache.put(filename, "let rent = 85000\n", &nodes());
+
+        let path = cache.path(filename);
+        let serialized = std::fs::read_to_string(&path).expect("Failed.");
+        assert!(serialized.contains(&current), "Invalid test case.");
+        std::fs::write(&path, serialized.replace(&current, &other)).expect("Failed.");
+
+        assert!(cache.get(filename, "let rent = 85000\n").is_none());
+    }
+
+    #[test]
+    fn test_ignores_corrupt_entries() {
+        let cache = cache("corrupt");
+        let filename = Path::new("rent.boki");
+        cache.put(filename, "let rent = 85000\n", &nodes());
+        std::fs::write(cache.path(filename), "{").expect("Failed.");
+
+        assert!(cache.get(filename, "let rent = 85000\n").is_none());
+    }
+}
@@ -1,27 +1,18 @@
-use boki::{common_errors, compile};
+use boki::common_errors;
 use std::path::PathBuf;
-use std::rc::Rc;
-
-#[derive(Debug)]
-pub struct FileCompileError {
-    error: compile::CompilationError,
-}
-
-impl std::fmt::Display for FileCompileError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "TODO: {:#?}", self.error)
-    }
-}
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub enum Error {
     IO {
-        filename: Rc<PathBuf>,
+        filename: Arc<PathBuf>,
         error: std::io::Error,
     },
     Lexer(common_errors::FileLexError),
     Parser(common_errors::FileParseError),
-    Compiler(FileCompileError),
+    Compiler(common_errors::FileCompileError),
+    /// An amount computed from the compiled journal, e.g. a total in a report, does not fit.
+    AmountOutOfRange(String),
 }
 
 impl From<common_errors::FileLexError> for Box<Error> {
@@ -36,22 +27,30 @@ impl From<common_errors::FileParseError> for Box<Error> {
     }
 }
 
+impl From<common_errors::FileCompileError> for Box<Error> {
+    fn from(value: common_errors::FileCompileError) -> Self {
+        Box::new(Error::Compiler(value))
+    }
+}
+
 impl crate::error::CLIError for Error {
     fn format(&self) -> String {
         match &self {
             Self::Lexer(e) => format!("{e}"),
             Self::Parser(e) => format!("{e}"),
-            _ => todo!(),
+            Self::Compiler(e) => format!("{e}"),
+            Self::IO { filename, error } => format!("{}: {error}", filename.display()),
+            Self::AmountOutOfRange(x) => format!("Amount is out of range: {x}"),
         }
     }
 }
 
-pub fn map_io_error(filename: Rc<PathBuf>) -> impl FnOnce(std::io::Error) -> Box<Error> {
+pub fn map_io_error(filename: Arc<PathBuf>) -> impl FnOnce(std::io::Error) -> Box<Error> {
     move |error| Box::new(Error::IO { filename, error })
 }
 
-pub fn map_compile_error() -> impl FnOnce(compile::CompilationError) -> Box<Error> {
-    |error| Box::new(Error::Compiler(FileCompileError { error }))
+pub fn out_of_range(what: String) -> Box<Error> {
+    Box::new(Error::AmountOutOfRange(what))
 }
 
 pub fn map_serde_error() -> impl FnOnce(serde_json::Error) -> Box<Error> {
@@ -11,18 +11,23 @@
 //! The caller should catch that error and re-attepmt parsing
 //! when more tokens are available.
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use boki::parsing::Parser;
 use boki::{ast, lex, parsing, tokens};
 
+mod account;
+mod auto_rule;
+mod budget;
+mod scope;
 mod set_attributes;
 mod transaction;
+mod variable;
 
-fn fold_tokens(
-    a: (Vec<usize>, Vec<lex::DecoratedToken>),
-    x: (usize, lex::DecoratedToken),
-) -> (Vec<usize>, Vec<lex::DecoratedToken>) {
+fn fold_tokens<'a>(
+    a: (Vec<usize>, Vec<&'a lex::DecoratedToken>),
+    x: (usize, &'a lex::DecoratedToken),
+) -> (Vec<usize>, Vec<&'a lex::DecoratedToken>) {
     let (mut token_map, mut tokens) = a;
     let (i, t) = x;
 
@@ -89,8 +94,43 @@ fn parse_set_attribute(scanner: &mut parsing::TokenScanner) -> parsing::ParserRe
         .map(|(x, y)| ast::ASTNode::SetAttribute(x, y))
 }
 
+fn parse_auto_rule(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<ast::ASTNode> {
+    auto_rule::AutoRuleParser::parse(scanner).map(ast::ASTNode::AutoRule)
+}
+
+fn parse_budget(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<ast::ASTNode> {
+    budget::BudgetParser::parse(scanner).map(ast::ASTNode::Budget)
+}
+
+fn parse_account_declaration(
+    scanner: &mut parsing::TokenScanner,
+) -> parsing::ParserResult<ast::ASTNode> {
+    account::AccountParser::parse(scanner).map(ast::ASTNode::AccountDeclaration)
+}
+
+fn parse_with(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<ast::ASTNode> {
+    scope::ScopeParser::parse_with(scanner).map(ast::ASTNode::With)
+}
+
+fn parse_end(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<ast::ASTNode> {
+    scope::ScopeParser::parse_end(scanner).map(|_| ast::ASTNode::End)
+}
+
+fn parse_let(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<ast::ASTNode> {
+    variable::VariableParser::parse(scanner).map(|(x, y)| ast::ASTNode::Let(x, y))
+}
+
 fn parse_node(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<ast::ASTNode> {
-    let parsers = [parse_transaction, parse_set_attribute];
+    let parsers = [
+        parse_transaction,
+        parse_set_attribute,
+        parse_auto_rule,
+        parse_budget,
+        parse_account_declaration,
+        parse_with,
+        parse_end,
+        parse_let,
+    ];
     let node = parsing::one_of(&parsers).parse(scanner).map_err(|e| {
         let parsing::ParserErrorDetails::BranchingError(_, errs) = &e.details else {
             panic!("This should never happen.")
@@ -110,7 +150,7 @@ fn parse_node(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<ast:
     Ok(node)
 }
 
-fn rewrite_locations(token_map: Rc<[usize]>, error: &mut parsing::ParserError) {
+fn rewrite_locations(token_map: Arc<[usize]>, error: &mut parsing::ParserError) {
     error.location = *token_map
         .as_ref()
         .get(error.location)
@@ -129,18 +169,20 @@ fn rewrite_locations(token_map: Rc<[usize]>, error: &mut parsing::ParserError) {
     };
 }
 
-pub fn parse_tokens(tokens: Rc<[lex::DecoratedToken]>) -> parsing::ParserResult<Vec<ast::ASTNode>> {
+/// Parses all nodes, each along with the index of the token it starts at.
+pub fn parse_tokens(
+    tokens: Arc<[lex::DecoratedToken]>,
+) -> parsing::ParserResult<Vec<(usize, ast::ASTNode)>> {
     let (token_map, folded_tokens) = tokens
         .iter()
-        .cloned()
         .enumerate()
         .fold((vec![], vec![]), fold_tokens);
 
-    let token_map: Rc<[usize]> = Rc::from(token_map.as_ref());
+    let token_map: Arc<[usize]> = Arc::from(token_map.as_ref());
 
     let raw_tokens: Vec<tokens::Token> = folded_tokens.iter().map(|x| x.token().clone()).collect();
     let mut scanner = parsing::TokenScanner::from_slice(&raw_tokens);
-    let mut nodes: Vec<ast::ASTNode> = vec![];
+    let mut nodes: Vec<(usize, ast::ASTNode)> = vec![];
 
     parse_initial_whitespace_and_comments(&mut scanner).map_err(|mut e| {
         rewrite_locations(token_map.clone(), &mut e);
@@ -151,11 +193,12 @@ pub fn parse_tokens(tokens: Rc<[lex::DecoratedToken]>) -> parsing::ParserResult<
         if let Some(tokens::Token::Eof) = scanner.peek() {
             break;
         }
+        let start = token_map[scanner.tell()];
         let node = parse_node(&mut scanner).map_err(|mut e| {
             rewrite_locations(token_map.clone(), &mut e);
             e
         })?;
-        nodes.push(node);
+        nodes.push((start, node));
     }
 
     Ok(nodes)
@@ -165,6 +208,7 @@ pub fn parse_tokens(tokens: Rc<[lex::DecoratedToken]>) -> parsing::ParserResult<
 mod test {
     use super::*;
     use boki::parsing;
+    use proptest::prelude::*;
 
     #[test]
     fn test_no_tokens() {
@@ -178,30 +222,42 @@ mod test {
 
     #[test]
     fn test_single_transaction() {
-        let ts = chrono::DateTime::parse_from_rfc3339("2026-01-01T00:00:00.000+09:00").unwrap();
-        let mut scanner = parsing::TokenScanner::from_slice(&[
+        let ts = chrono::DateTime::parse_from_rfc3339("2026-01-01T00:00:00.000+09:00")
+            .unwrap()
+            .into();
+        let tokens = [
             tokens::Token::Timestamp(ts),
             tokens::Token::LineSeparator,
             tokens::Token::Indent,
-            tokens::Token::Identifier("asset".to_string()),
+            tokens::Token::Identifier("asset".into()),
             tokens::Token::AccountSeparator,
-            tokens::Token::Identifier("cce".to_string()),
+            tokens::Token::Identifier("cce".into()),
             tokens::Token::AccountSeparator,
-            tokens::Token::Identifier("cash".to_string()),
+            tokens::Token::Identifier("cash".into()),
             tokens::Token::PostingSeparator,
-            tokens::Token::Identifier("JPY".to_string()),
+            tokens::Token::Identifier("JPY".into()),
             tokens::Token::PostingSeparator,
             tokens::Token::Amount(1000),
             tokens::Token::LineSeparator,
             tokens::Token::Indent,
-            tokens::Token::Identifier("expense".to_string()),
+            tokens::Token::Identifier("expense".into()),
             tokens::Token::PostingSeparator,
-            tokens::Token::Identifier("JPY".to_string()),
+            tokens::Token::Identifier("JPY".into()),
             tokens::Token::PostingSeparator,
             tokens::Token::Amount(1000),
             tokens::Token::LineSeparator,
-        ]);
+        ];
+        let mut scanner = parsing::TokenScanner::from_slice(&tokens);
         let node = parse_node(&mut scanner).expect("Failed.");
         assert!(matches!(node, ast::ASTNode::Transaction(_)));
     }
+
+    proptest! {
+        #[test]
+        fn test_never_panics(content in crate::cli::testing::journal_fragments()) {
+            if let Ok(tokens) = lex::lex_string(&content) {
+                let _ = parse_tokens(tokens.into());
+            }
+        }
+    }
 }
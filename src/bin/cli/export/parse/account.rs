@@ -0,0 +1,47 @@
+use boki::ast;
+use boki::tokens::Keyword;
+
+use boki::parsing;
+
+use super::transaction::TransactionParser;
+
+pub struct AccountParser;
+
+impl AccountParser {
+    pub fn parse(
+        scanner: &mut parsing::TokenScanner,
+    ) -> parsing::ParserResult<ast::AccountDeclaration> {
+        parsing::parse_keyword(scanner, Keyword::Account)?;
+        let account = TransactionParser::parse_account(scanner)?;
+        parsing::parse_posting_separator(scanner)?;
+        let account_type = parsing::parse_identifier(scanner)?;
+        parsing::parse_line_separator(scanner)?;
+
+        Ok(ast::AccountDeclaration {
+            account,
+            account_type,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use boki::parsing::TokenScanner;
+    use boki::tokens::Token;
+
+    #[test]
+    fn test_simple() {
+        let tokens = [
+            Token::Keyword(Keyword::Account),
+            Token::Identifier("cost".into()),
+            Token::PostingSeparator,
+            Token::Identifier("expense".into()),
+            Token::LineSeparator,
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        let result = AccountParser::parse(&mut scanner).expect("Failed.");
+        assert_eq!(result.account, "cost");
+        assert_eq!(result.account_type, "expense");
+    }
+}
@@ -0,0 +1,146 @@
+use boki::ast;
+use boki::tokens::Keyword;
+
+use boki::parsing::{self, Parser, ParserError};
+
+use super::transaction::TransactionParser;
+
+pub struct AutoRuleParser;
+
+impl AutoRuleParser {
+    fn parse_fixed(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<ast::AutoAmount> {
+        parsing::parse_expression(scanner).map(ast::AutoAmount::Fixed)
+    }
+
+    fn parse_percentage(
+        scanner: &mut parsing::TokenScanner,
+    ) -> parsing::ParserResult<ast::AutoAmount> {
+        parsing::parse_percentage(scanner).map(ast::AutoAmount::Percentage)
+    }
+
+    fn parse_posting(
+        scanner: &mut parsing::TokenScanner,
+    ) -> parsing::ParserResult<ast::AutoPosting> {
+        let account = TransactionParser::parse_account(scanner)?;
+        parsing::parse_posting_separator(scanner)?;
+        let commodity = parsing::optional(TransactionParser::parse_commodity).parse(scanner)?;
+        parsing::parse_posting_separator(scanner)?;
+        let amount =
+            parsing::one_of(&[Self::parse_fixed, Self::parse_percentage]).parse(scanner)?;
+        parsing::parse_line_separator(scanner)?;
+
+        Ok(ast::AutoPosting {
+            account,
+            commodity,
+            amount,
+        })
+    }
+
+    pub fn parse(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<ast::AutoRule> {
+        parsing::parse_keyword(scanner, Keyword::Auto)?;
+        let account = parsing::optional(TransactionParser::parse_account).parse(scanner)?;
+        parsing::parse_line_separator(scanner)?;
+        let attributes = parsing::optional(TransactionParser::parse_attributes)
+            .parse(scanner)
+            .map(|x| x.unwrap_or_default())?;
+
+        let mut postings = vec![];
+        loop {
+            let i = scanner.tell();
+            if parsing::parse_indent(scanner).is_err() {
+                scanner.seek(i)?;
+                break;
+            }
+            let p = Self::parse_posting(scanner).map_err(|e| ParserError {
+                location: i,
+                details: parsing::ParserErrorDetails::Nested(
+                    "Encountered invalid posting".to_string(),
+                    Box::new(e),
+                ),
+            })?;
+            postings.push(p);
+        }
+
+        Ok(ast::AutoRule {
+            account,
+            attributes,
+            postings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use boki::parsing::TokenScanner;
+    use boki::tokens::Token;
+
+    #[test]
+    fn test_simple() {
+        let tokens = [
+            Token::Keyword(Keyword::Auto),
+            Token::Identifier("expense".into()),
+            Token::LineSeparator,
+            Token::Indent,
+            Token::Identifier("tax".into()),
+            Token::PostingSeparator,
+            Token::PostingSeparator,
+            Token::Percentage(1000),
+            Token::LineSeparator,
+            Token::Indent,
+            Token::Identifier("expense".into()),
+            Token::PostingSeparator,
+            Token::Identifier("JPY".into()),
+            Token::PostingSeparator,
+            Token::Amount(-100),
+            Token::LineSeparator,
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        let result = AutoRuleParser::parse(&mut scanner).expect("Failed.");
+        assert_eq!(result.account.as_deref(), Some("expense"));
+        assert_eq!(result.postings.len(), 2);
+        assert!(matches!(
+            result.postings[0].amount,
+            ast::AutoAmount::Percentage(1000)
+        ));
+        assert_eq!(result.postings[1].commodity.as_deref(), Some("JPY"));
+        assert!(matches!(
+            result.postings[1].amount,
+            ast::AutoAmount::Fixed(ast::Expression::Amount(-100))
+        ));
+    }
+
+    #[test]
+    fn test_without_account() {
+        let tokens = [
+            Token::Keyword(Keyword::Auto),
+            Token::LineSeparator,
+            Token::Indent,
+            Token::Identifier("expense/fee".into()),
+            Token::PostingSeparator,
+            Token::PostingSeparator,
+            Token::Amount(100),
+            Token::LineSeparator,
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        let result = AutoRuleParser::parse(&mut scanner).expect("Failed.");
+        assert!(result.account.is_none());
+        assert_eq!(result.postings.len(), 1);
+    }
+
+    #[test]
+    fn test_posting_without_amount_fails() {
+        let tokens = [
+            Token::Keyword(Keyword::Auto),
+            Token::Identifier("expense".into()),
+            Token::LineSeparator,
+            Token::Indent,
+            Token::Identifier("tax".into()),
+            Token::PostingSeparator,
+            Token::PostingSeparator,
+            Token::LineSeparator,
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        AutoRuleParser::parse(&mut scanner).expect_err("Should have failed.");
+    }
+}
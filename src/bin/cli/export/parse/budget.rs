@@ -0,0 +1,57 @@
+use boki::ast;
+use boki::tokens::Keyword;
+
+use boki::parsing::{self, Parser};
+
+use super::transaction::TransactionParser;
+
+pub struct BudgetParser;
+
+impl BudgetParser {
+    pub fn parse(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<ast::Budget> {
+        parsing::parse_keyword(scanner, Keyword::Budget)?;
+        let period = parsing::parse_identifier(scanner)?;
+        let account = TransactionParser::parse_account(scanner)?;
+        parsing::parse_posting_separator(scanner)?;
+        let commodity = parsing::optional(TransactionParser::parse_commodity).parse(scanner)?;
+        parsing::parse_posting_separator(scanner)?;
+        let amount = parsing::parse_expression(scanner)?;
+        parsing::parse_line_separator(scanner)?;
+
+        Ok(ast::Budget {
+            period,
+            account,
+            commodity,
+            amount,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use boki::parsing::TokenScanner;
+    use boki::tokens::Token;
+
+    #[test]
+    fn test_simple() {
+        let tokens = [
+            Token::Keyword(Keyword::Budget),
+            Token::Identifier("monthly".into()),
+            Token::Identifier("expense".into()),
+            Token::AccountSeparator,
+            Token::Identifier("food".into()),
+            Token::PostingSeparator,
+            Token::Identifier("JPY".into()),
+            Token::PostingSeparator,
+            Token::Amount(60000),
+            Token::LineSeparator,
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        let result = BudgetParser::parse(&mut scanner).expect("Failed.");
+        assert_eq!(result.period, "monthly");
+        assert_eq!(result.account, "expense/food");
+        assert_eq!(result.commodity.as_deref(), Some("JPY"));
+        assert_eq!(result.amount, 60000.into());
+    }
+}
@@ -0,0 +1,53 @@
+use boki::tokens::Keyword;
+
+use boki::parsing::{self, TokenScanner};
+
+use super::transaction::TransactionParser;
+
+pub struct ScopeParser;
+
+impl ScopeParser {
+    pub fn parse_with(scanner: &mut TokenScanner) -> parsing::ParserResult<serde_yaml::Mapping> {
+        parsing::parse_keyword(scanner, Keyword::With)?;
+        parsing::parse_line_separator(scanner)?;
+        TransactionParser::parse_attributes(scanner)
+    }
+
+    pub fn parse_end(scanner: &mut TokenScanner) -> parsing::ParserResult<()> {
+        parsing::parse_keyword(scanner, Keyword::End)?;
+        parsing::parse_line_separator(scanner)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use boki::tokens::Token;
+
+    #[test]
+    fn test_with() {
+        let mut attributes = serde_yaml::Mapping::new();
+        attributes.insert("trip".into(), "honeymoon".into());
+        let tokens = [
+            Token::Keyword(Keyword::With),
+            Token::LineSeparator,
+            Token::YamlMatter(attributes.clone().into()),
+            Token::LineSeparator,
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        let result = ScopeParser::parse_with(&mut scanner).expect("Failed.");
+        assert_eq!(result, attributes);
+    }
+
+    #[test]
+    fn test_with_without_attributes_fails() {
+        let tokens = [
+            Token::Keyword(Keyword::With),
+            Token::LineSeparator,
+            Token::Keyword(Keyword::End),
+            Token::LineSeparator,
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        ScopeParser::parse_with(&mut scanner).expect_err("Should have failed.");
+    }
+}
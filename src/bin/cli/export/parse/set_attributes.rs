@@ -1,6 +1,7 @@
+use boki::ast;
 use boki::tokens::Keyword;
 
-use boki::parsing::{self, TokenScanner};
+use boki::parsing::{self, Parser, TokenScanner};
 
 pub struct SetAttributeParser;
 
@@ -9,10 +10,33 @@ impl SetAttributeParser {
         SetAttributeParser
     }
 
-    pub fn parse(&self, scanner: &mut TokenScanner) -> parsing::ParserResult<(String, String)> {
+    /// Parses a value such as `JPY` or `Asia/Tokyo`.
+    fn parse_path(scanner: &mut TokenScanner) -> parsing::ParserResult<ast::Value> {
+        let root = parsing::parse_identifier(scanner)?;
+        let rest = parsing::many(parsing::preceded(
+            parsing::parse_account_separator,
+            parsing::parse_identifier,
+        ))
+        .parse(scanner)?;
+
+        Ok(ast::Value::Identifier(
+            rest.into_iter().fold(root, |a, p| a + "/" + &p),
+        ))
+    }
+
+    fn parse_string(scanner: &mut TokenScanner) -> parsing::ParserResult<ast::Value> {
+        parsing::parse_string(scanner).map(ast::Value::String)
+    }
+
+    fn parse_integer(scanner: &mut TokenScanner) -> parsing::ParserResult<ast::Value> {
+        parsing::parse_amount(scanner).map(ast::Value::Integer)
+    }
+
+    pub fn parse(&self, scanner: &mut TokenScanner) -> parsing::ParserResult<(String, ast::Value)> {
         parsing::parse_keyword(scanner, Keyword::Set)?;
         let name = parsing::parse_identifier(scanner)?;
-        let value = parsing::parse_identifier(scanner)?;
+        let value = parsing::one_of(&[Self::parse_path, Self::parse_string, Self::parse_integer])
+            .parse(scanner)?;
         parsing::parse_line_separator(scanner)?;
 
         Ok((name, value))
@@ -26,16 +50,45 @@ mod test {
 
     #[test]
     fn test_simple() {
-        let mut scanner = TokenScanner::from_slice(&[
+        let tokens = [
             Token::Keyword(Keyword::Set),
-            Token::Identifier("default_commodity".to_string()),
-            Token::Identifier("JPY".to_string()),
+            Token::Identifier("default_commodity".into()),
+            Token::Identifier("JPY".into()),
             Token::LineSeparator,
-        ]);
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
         let result = SetAttributeParser::new()
             .parse(&mut scanner)
             .expect("Failed.");
         assert_eq!(result.0, "default_commodity");
-        assert_eq!(result.1, "JPY");
+        assert_eq!(result.1, ast::Value::Identifier("JPY".to_string()));
+    }
+
+    #[rstest::rstest]
+    #[case::path(
+        vec![
+            Token::Identifier("Asia".into()),
+            Token::AccountSeparator,
+            Token::Identifier("Tokyo".into()),
+        ],
+        ast::Value::Identifier("Asia/Tokyo".to_string())
+    )]
+    #[case::string(
+        vec![Token::String("+09:00".into())],
+        ast::Value::String("+09:00".to_string())
+    )]
+    #[case::integer(vec![Token::Amount(4)], ast::Value::Integer(4))]
+    fn test_values(#[case] value: Vec<Token>, #[case] expected: ast::Value) {
+        let mut tokens = vec![
+            Token::Keyword(Keyword::Set),
+            Token::Identifier("default_timezone".into()),
+        ];
+        tokens.extend(value);
+        tokens.push(Token::LineSeparator);
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        let result = SetAttributeParser::new()
+            .parse(&mut scanner)
+            .expect("Failed.");
+        assert_eq!(result.1, expected);
     }
 }
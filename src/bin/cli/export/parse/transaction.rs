@@ -5,7 +5,7 @@ use boki::parsing::{self, Parser, ParserError};
 pub struct TransactionParser;
 
 impl TransactionParser {
-    fn parse_transaction_attributes(
+    pub(super) fn parse_attributes(
         scanner: &mut parsing::TokenScanner,
     ) -> parsing::ParserResult<serde_yaml::Mapping> {
         parsing::terminated(parsing::parse_yaml_matter, parsing::parse_line_separator)
@@ -16,23 +16,48 @@ impl TransactionParser {
         scanner: &mut parsing::TokenScanner,
     ) -> parsing::ParserResult<ast::TransactionHeader> {
         let timestamp = parsing::parse_timestamp(scanner)?;
+        let status = parsing::optional(parsing::parse_status).parse(scanner)?;
+        let description = parsing::optional(parsing::parse_description).parse(scanner)?;
         parsing::parse_line_separator(scanner)?;
-        let attributes = parsing::optional(Self::parse_transaction_attributes)
+        let attributes = parsing::optional(Self::parse_attributes)
             .parse(scanner)
             .map(|x| x.unwrap_or_default())?;
         Ok(ast::TransactionHeader {
             timestamp,
+            status,
+            description,
             attributes,
         })
     }
 
+    /// Parses a quoted account segment, e.g. `"my bank"`, which may contain anything but a `/`.
+    fn parse_quoted_segment(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<String> {
+        let location = scanner.tell();
+        let segment = parsing::parse_string(scanner)?;
+        if segment.is_empty() || segment.contains('/') {
+            return Err(ParserError {
+                location,
+                details: parsing::ParserErrorDetails::Other(
+                    "A quoted account segment cannot be empty or contain a `/`.".to_string(),
+                ),
+            });
+        }
+
+        Ok(segment)
+    }
+
+    fn parse_segment(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<String> {
+        parsing::one_of(&[parsing::parse_identifier, Self::parse_quoted_segment]).parse(scanner)
+    }
+
     fn parse_subaccount(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<String> {
-        parsing::preceded(parsing::parse_account_separator, parsing::parse_identifier)
-            .parse(scanner)
+        parsing::preceded(parsing::parse_account_separator, Self::parse_segment).parse(scanner)
     }
 
-    fn parse_account(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<String> {
-        let root = parsing::parse_identifier(scanner)?;
+    pub(super) fn parse_account(
+        scanner: &mut parsing::TokenScanner,
+    ) -> parsing::ParserResult<String> {
+        let root = Self::parse_segment(scanner)?;
         let rest = parsing::many(Self::parse_subaccount).parse(scanner)?;
 
         let acc = rest.into_iter().fold(root, |a, p| a + "/" + &p);
@@ -40,7 +65,9 @@ impl TransactionParser {
         Ok(acc)
     }
 
-    fn parse_commodity(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<String> {
+    pub(super) fn parse_commodity(
+        scanner: &mut parsing::TokenScanner,
+    ) -> parsing::ParserResult<String> {
         parsing::parse_identifier(scanner)
     }
 
@@ -49,13 +76,23 @@ impl TransactionParser {
         parsing::parse_posting_separator(scanner)?;
         let commodity = parsing::optional(Self::parse_commodity).parse(scanner)?;
         parsing::parse_posting_separator(scanner)?;
-        let amount = parsing::optional(parsing::parse_amount).parse(scanner)?;
+        let amount = parsing::optional(parsing::parse_expression).parse(scanner)?;
+        let date = parsing::optional(parsing::preceded(
+            parsing::parse_posting_separator,
+            parsing::parse_timestamp,
+        ))
+        .parse(scanner)?;
         parsing::parse_line_separator(scanner)?;
+        let attributes = parsing::optional(Self::parse_attributes)
+            .parse(scanner)
+            .map(|x| x.unwrap_or_default())?;
 
         Ok(ast::Posting {
             account,
             commodity,
             amount,
+            attributes,
+            date,
         })
     }
 
@@ -87,16 +124,19 @@ impl TransactionParser {
 mod test {
     use super::TransactionParser;
     use boki::parsing::TokenScanner;
-    use boki::tokens::{Timestamp, Token};
+    use boki::tokens::{Timestamp, Token, TransactionStatus};
 
     fn sample_timestamp() -> Timestamp {
-        Timestamp::parse_from_rfc3339("2026-01-02T03:04:05.000+09:00").unwrap()
+        chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05.000+09:00")
+            .unwrap()
+            .into()
     }
 
     #[test]
     fn test_header_simple() {
         let ts = sample_timestamp();
-        let mut scanner = TokenScanner::from_slice(&[Token::Timestamp(ts), Token::LineSeparator]);
+        let tokens = [Token::Timestamp(ts), Token::LineSeparator];
+        let mut scanner = TokenScanner::from_slice(&tokens);
         let result = TransactionParser::parse_header(&mut scanner).expect("Failed.");
         assert_eq!(result.timestamp, sample_timestamp());
     }
@@ -104,95 +144,156 @@ mod test {
     #[test]
     fn test_header_attributes() {
         let ts = sample_timestamp();
-        let mut scanner = TokenScanner::from_slice(&[
+        let tokens = [
             Token::Timestamp(ts),
             Token::LineSeparator,
-            Token::YamlMatter(serde_yaml::Mapping::default()),
+            Token::YamlMatter(Default::default()),
             Token::LineSeparator,
-        ]);
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
         let result = TransactionParser::parse_header(&mut scanner).expect("Failed.");
         assert_eq!(result.timestamp, sample_timestamp());
     }
 
+    #[test]
+    fn test_header_status_and_description() {
+        let ts = sample_timestamp();
+        let tokens = [
+            Token::Timestamp(ts),
+            Token::Status(TransactionStatus::Cleared),
+            Token::Description("Coffee shop".into()),
+            Token::LineSeparator,
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        let result = TransactionParser::parse_header(&mut scanner).expect("Failed.");
+        assert_eq!(result.status, Some(TransactionStatus::Cleared));
+        assert_eq!(result.description.as_deref(), Some("Coffee shop"));
+    }
+
     #[test]
     fn test_posting_simple() {
-        let mut scanner = TokenScanner::from_slice(&[
-            Token::Identifier("asset".to_string()),
+        let tokens = [
+            Token::Identifier("asset".into()),
             Token::AccountSeparator,
-            Token::Identifier("cce".to_string()),
+            Token::Identifier("cce".into()),
             Token::AccountSeparator,
-            Token::Identifier("cash".to_string()),
+            Token::Identifier("cash".into()),
             Token::PostingSeparator,
-            Token::Identifier("JPY".to_string()),
+            Token::Identifier("JPY".into()),
             Token::PostingSeparator,
             Token::Amount(1000),
             Token::LineSeparator,
-        ]);
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
         let result = TransactionParser::parse_posting(&mut scanner).expect("Failed.");
         assert_eq!(result.account, "asset/cce/cash".to_string());
         assert_eq!(result.commodity, Some("JPY".to_string()));
-        assert_eq!(result.amount, Some(1000));
+        assert_eq!(result.amount, Some(1000.into()));
+    }
+
+    #[rstest::rstest]
+    #[case::quoted(Token::String("my bank".into()), Some("資産/my bank"))]
+    #[case::quoted_with_separator(Token::String("a/b".into()), None)]
+    #[case::empty(Token::String("".into()), None)]
+    fn test_posting_quoted_account(#[case] segment: Token, #[case] expected: Option<&str>) {
+        let tokens = [
+            Token::Identifier("資産".into()),
+            Token::AccountSeparator,
+            segment,
+            Token::PostingSeparator,
+            Token::PostingSeparator,
+            Token::Amount(1000),
+            Token::LineSeparator,
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        let result = TransactionParser::parse_posting(&mut scanner);
+        assert_eq!(result.ok().map(|x| x.account).as_deref(), expected);
     }
 
     #[test]
     fn test_posting_omitted_commodity() {
-        let mut scanner = TokenScanner::from_slice(&[
-            Token::Identifier("asset".to_string()),
+        let tokens = [
+            Token::Identifier("asset".into()),
             Token::AccountSeparator,
-            Token::Identifier("cce".to_string()),
+            Token::Identifier("cce".into()),
             Token::AccountSeparator,
-            Token::Identifier("cash".to_string()),
+            Token::Identifier("cash".into()),
             Token::PostingSeparator,
             Token::PostingSeparator,
             Token::Amount(1000),
             Token::LineSeparator,
-        ]);
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
         let result = TransactionParser::parse_posting(&mut scanner).expect("Failed.");
         assert_eq!(result.commodity, None);
     }
 
     #[test]
     fn test_posting_omitted_amount() {
-        let mut scanner = TokenScanner::from_slice(&[
-            Token::Identifier("asset".to_string()),
+        let tokens = [
+            Token::Identifier("asset".into()),
             Token::AccountSeparator,
-            Token::Identifier("cce".to_string()),
+            Token::Identifier("cce".into()),
             Token::AccountSeparator,
-            Token::Identifier("cash".to_string()),
+            Token::Identifier("cash".into()),
             Token::PostingSeparator,
-            Token::Identifier("JPY".to_string()),
+            Token::Identifier("JPY".into()),
             Token::PostingSeparator,
             Token::LineSeparator,
-        ]);
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
         let result = TransactionParser::parse_posting(&mut scanner).expect("Failed.");
         assert_eq!(result.amount, None);
     }
 
+    #[test]
+    fn test_posting_date_and_attributes() {
+        let mut attributes = serde_yaml::Mapping::new();
+        attributes.insert("receipt".into(), "123".into());
+        let tokens = [
+            Token::Identifier("asset".into()),
+            Token::PostingSeparator,
+            Token::Identifier("JPY".into()),
+            Token::PostingSeparator,
+            Token::Amount(1000),
+            Token::PostingSeparator,
+            Token::Timestamp(sample_timestamp()),
+            Token::LineSeparator,
+            Token::YamlMatter(attributes.clone().into()),
+            Token::LineSeparator,
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        let result = TransactionParser::parse_posting(&mut scanner).expect("Failed.");
+        assert_eq!(result.date, Some(sample_timestamp()));
+        assert_eq!(result.attributes, attributes);
+    }
+
     #[test]
     fn test_simple() {
         let ts = sample_timestamp();
-        let mut scanner = TokenScanner::from_slice(&[
+        let tokens = [
             Token::Timestamp(ts),
             Token::LineSeparator,
             Token::Indent,
-            Token::Identifier("asset".to_string()),
+            Token::Identifier("asset".into()),
             Token::AccountSeparator,
-            Token::Identifier("cce".to_string()),
+            Token::Identifier("cce".into()),
             Token::AccountSeparator,
-            Token::Identifier("cash".to_string()),
+            Token::Identifier("cash".into()),
             Token::PostingSeparator,
-            Token::Identifier("JPY".to_string()),
+            Token::Identifier("JPY".into()),
             Token::PostingSeparator,
             Token::Amount(1000),
             Token::LineSeparator,
             Token::Indent,
-            Token::Identifier("expense".to_string()),
+            Token::Identifier("expense".into()),
             Token::PostingSeparator,
-            Token::Identifier("JPY".to_string()),
+            Token::Identifier("JPY".into()),
             Token::PostingSeparator,
             Token::Amount(1000),
             Token::LineSeparator,
-        ]);
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
         TransactionParser::parse(&mut scanner).expect("Failed.");
     }
 
@@ -201,28 +302,29 @@ mod test {
         use boki::parsing::ParserErrorDetails;
 
         let ts = sample_timestamp();
-        let mut scanner = TokenScanner::from_slice(&[
+        let tokens = [
             Token::Timestamp(ts),
             Token::LineSeparator,
             Token::Indent,
-            Token::Identifier("asset".to_string()),
+            Token::Identifier("asset".into()),
             Token::AccountSeparator,
-            Token::Identifier("cce".to_string()),
+            Token::Identifier("cce".into()),
             Token::AccountSeparator,
-            Token::Identifier("cash".to_string()),
+            Token::Identifier("cash".into()),
             Token::PostingSeparator,
-            Token::Identifier("JPY".to_string()),
+            Token::Identifier("JPY".into()),
             // Token::PostingSeparator,
             Token::Amount(1000),
             Token::LineSeparator,
             Token::Indent,
-            Token::Identifier("expense".to_string()),
+            Token::Identifier("expense".into()),
             Token::PostingSeparator,
-            Token::Identifier("JPY".to_string()),
+            Token::Identifier("JPY".into()),
             Token::PostingSeparator,
             Token::Amount(1000),
             Token::LineSeparator,
-        ]);
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
         let err = TransactionParser::parse(&mut scanner).expect_err("Should have failed.");
         assert_eq!(err.location, 2);
         let ParserErrorDetails::Nested(_, nested) = err.details else {
@@ -231,7 +333,7 @@ mod test {
         assert_eq!(nested.location, 10);
         assert!(matches!(
             nested.details,
-            ParserErrorDetails::ExpectedSomethingElse(_1, _2)
+            ParserErrorDetails::ExpectedSomethingElse(_, _)
         ));
     }
 }
@@ -0,0 +1,54 @@
+use boki::ast;
+use boki::tokens::Keyword;
+
+use boki::parsing::{self, TokenScanner};
+
+pub struct VariableParser;
+
+impl VariableParser {
+    /// Parses a definition such as `let rent = 85000`.
+    pub fn parse(scanner: &mut TokenScanner) -> parsing::ParserResult<(String, ast::Expression)> {
+        parsing::parse_keyword(scanner, Keyword::Let)?;
+        let name = parsing::parse_identifier(scanner)?;
+        parsing::parse_equals(scanner)?;
+        let value = parsing::parse_expression(scanner)?;
+        parsing::parse_line_separator(scanner)?;
+
+        Ok((name, value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use boki::tokens::{Operator, Token};
+
+    #[test]
+    fn test_simple() {
+        let tokens = [
+            Token::Keyword(Keyword::Let),
+            Token::Identifier("share".into()),
+            Token::Equals,
+            Token::Identifier("rent".into()),
+            Token::Operator(Operator::Divide),
+            Token::Amount(3),
+            Token::LineSeparator,
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        let (name, value) = VariableParser::parse(&mut scanner).expect("Failed.");
+        assert_eq!(name, "share");
+        assert_eq!(value.to_string(), "rent / 3");
+    }
+
+    #[test]
+    fn test_missing_value_fails() {
+        let tokens = [
+            Token::Keyword(Keyword::Let),
+            Token::Identifier("rent".into()),
+            Token::Equals,
+            Token::LineSeparator,
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        VariableParser::parse(&mut scanner).expect_err("Should have failed.");
+    }
+}
@@ -5,23 +5,30 @@ use boki::parsing::TokenScanner;
 use boki::tokens;
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::rc::Rc;
+use std::sync::Arc;
 
 mod _ast;
+mod config;
 mod error;
 mod parse;
+mod sort;
 mod write;
 
 type Result<T> = std::result::Result<T, Box<error::Error>>;
 
-fn read_file(filename: Rc<PathBuf>) -> Result<Rc<str>> {
+fn read_file(filename: Arc<PathBuf>) -> Result<Arc<str>> {
     std::fs::read_to_string(filename.as_ref())
         .map_err(error::map_io_error(filename.clone()))
         .map(|x| x.into())
 }
 
-fn format_content(filename: Rc<PathBuf>, content: Rc<str>) -> Result<String> {
-    let decorated_tokens: Rc<[DecoratedToken]> = lex::lex_string(content.as_ref())
+pub fn format_content(
+    filename: Arc<PathBuf>,
+    content: Arc<str>,
+    style: &config::FormatStyle,
+    sort: bool,
+) -> Result<String> {
+    let decorated_tokens: Arc<[DecoratedToken]> = lex::lex_string(content.as_ref())
         .map_err(FileLexError::map_from_lexer_error(
             filename.clone(),
             content.clone(),
@@ -37,12 +44,31 @@ fn format_content(filename: Rc<PathBuf>, content: Rc<str>) -> Result<String> {
         FileParseError::map_from_parser_error(filename.clone(), content.clone(), decorated_tokens),
     )?;
 
-    let output = format!("{}", write::to_displayable(nodes.as_slice()));
+    let nodes = if sort {
+        sort::sort_transactions(nodes)
+    } else {
+        nodes
+    };
+
+    let output = format!("{}", write::to_displayable(nodes.as_slice(), style));
 
     Ok(output)
 }
 
-fn write_file(filename: Rc<PathBuf>, output: &str) -> Result<()> {
+/// Formats journal text which was generated by another command (e.g. `boki close`) in the default style.
+pub fn format_generated(content: &str) -> String {
+    use crate::error::CLIErrorResult;
+
+    format_content(
+        Arc::new(PathBuf::from("<generated>")),
+        content.into(),
+        &Default::default(),
+        false,
+    )
+    .or_panic()
+}
+
+fn write_file(filename: Arc<PathBuf>, output: &str) -> Result<()> {
     let mut f = std::fs::File::options()
         .write(true)
         .truncate(true)
@@ -54,22 +80,102 @@ fn write_file(filename: Rc<PathBuf>, output: &str) -> Result<()> {
     Ok(())
 }
 
-fn format_file(filename: &Path) -> Result<()> {
-    let filename = Rc::new(filename.to_path_buf());
-    let content = read_file(filename.clone())?;
-    let output = format_content(filename.clone(), content.clone())?;
-    write_file(filename, &output)?;
-    Ok(())
+fn unified_diff(filename: &Path, original: &str, formatted: &str) -> String {
+    let name = filename.to_string_lossy();
+    similar::TextDiff::from_lines(original, formatted)
+        .unified_diff()
+        .header(&name, &name)
+        .to_string()
+}
+
+/// Formats a single file (or stdin, if `filename` is [None]).
+///
+/// Returns whether the formatted content differs from the original one.
+fn format_file(filename: Option<&Path>, style: &config::FormatStyle, args: &Args) -> Result<bool> {
+    let (filename, content) = match filename {
+        Some(filename) => {
+            let filename = Arc::new(filename.to_path_buf());
+            let content = read_file(filename.clone())?;
+            (filename, content)
+        }
+        None => {
+            let filename = Arc::new(PathBuf::from(STDIN_FILENAME));
+            let content = read_stdin(filename.clone())?;
+            (filename, content)
+        }
+    };
+
+    let output = format_content(filename.clone(), content.clone(), style, args.sort)?;
+    let changed = output != content.as_ref();
+
+    if args.diff {
+        if changed {
+            print!("{}", unified_diff(&filename, &content, &output));
+        }
+    } else if args.check {
+        // Nothing to do here. The caller will report the file.
+    } else if filename.as_os_str() == STDIN_FILENAME {
+        print!("{output}");
+    } else if changed {
+        write_file(filename, &output)?;
+    }
+
+    Ok(changed)
+}
+
+const STDIN_FILENAME: &str = "-";
+
+fn read_stdin(filename: Arc<PathBuf>) -> Result<Arc<str>> {
+    std::io::read_to_string(std::io::stdin())
+        .map_err(error::map_io_error(filename))
+        .map(|x| x.into())
 }
 
 #[derive(clap::Args)]
 pub struct Args {
+    /// The files to format. If none are given (or the file is `-`), the journal is read from stdin
+    /// and the formatted result is written to stdout.
     files: Vec<PathBuf>,
+
+    /// Do not modify any files. Instead, exit with an error if any of them is not formatted.
+    #[arg(long)]
+    check: bool,
+
+    /// Do not modify any files. Instead, print a unified diff of the changes that would be made.
+    #[arg(long)]
+    diff: bool,
+
+    /// Reorder transactions chronologically. Transactions are never moved across other directives (e.g. `set`).
+    #[arg(long)]
+    sort: bool,
+
+    /// The configuration file to read the formatting style from.
+    /// Defaults to `boki.toml` in the current directory, if it exists.
+    #[arg(long)]
+    config: Option<PathBuf>,
 }
 
 pub fn run(args: &Args) -> Result<()> {
-    for file in &args.files {
-        format_file(file)?;
+    let style = config::load(args.config.as_deref())?;
+
+    let files: Vec<Option<&Path>> = if args.files.is_empty() {
+        vec![None]
+    } else {
+        args.files
+            .iter()
+            .map(|x| Some(x.as_path()).filter(|x| x.as_os_str() != STDIN_FILENAME))
+            .collect()
+    };
+
+    let mut unformatted = vec![];
+    for file in files {
+        if format_file(file, &style, args)? {
+            unformatted.push(file.unwrap_or(Path::new(STDIN_FILENAME)).to_path_buf());
+        }
+    }
+
+    if args.check && !unformatted.is_empty() {
+        return Err(Box::new(error::Error::Unformatted(unformatted)));
     }
 
     Ok(())
@@ -78,18 +184,353 @@ pub fn run(args: &Args) -> Result<()> {
 #[cfg(test)]
 mod test {
     use std::path::PathBuf;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
+    use boki::{lex, tokens};
+    use proptest::prelude::*;
+
+    use super::config;
     use crate::{cli::format::read_file, error::CLIErrorResult};
 
     #[test]
     fn test_smoke() {
-        let filename = Rc::new(PathBuf::from("src/bin/cli/format/input.boki"));
+        let filename = Arc::new(PathBuf::from("src/bin/cli/format/input.boki"));
         let content = read_file(filename.clone()).or_panic();
-        let formatted_str = super::format_content(filename.clone(), content.clone()).or_panic();
+        let formatted_str = super::format_content(
+            filename.clone(),
+            content.clone(),
+            &Default::default(),
+            false,
+        )
+        .or_panic();
         let rhs = std::fs::read_to_string("src/bin/cli/format/output.boki")
             .expect("Could not read output file.");
 
         assert_eq!(formatted_str, rhs);
     }
+
+    #[test]
+    fn test_check_does_not_modify_files() {
+        let args = super::Args {
+            files: vec![PathBuf::from("src/bin/cli/format/input.boki")],
+            check: true,
+            diff: false,
+            sort: false,
+            config: None,
+        };
+        let before = std::fs::read_to_string(&args.files[0]).expect("Failed.");
+
+        let err = super::run(&args).expect_err("Should have failed.");
+        assert!(matches!(*err, super::error::Error::Unformatted(_)));
+
+        let after = std::fs::read_to_string(&args.files[0]).expect("Failed.");
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn test_check_succeeds_on_formatted_file() {
+        let args = super::Args {
+            files: vec![PathBuf::from("src/bin/cli/format/output.boki")],
+            check: true,
+            diff: false,
+            sort: false,
+            config: None,
+        };
+        super::run(&args).or_panic();
+    }
+
+    #[test]
+    fn test_posting_date_and_attributes() {
+        let input = indoc::indoc! {"
+            2026-01-01
+              assets/card;JPY;-1000;2026-01-05 // card
+                ---
+                receipt: 123
+                ---
+              expense;;
+        "};
+        let expected = indoc::indoc! {"
+            2026-01-01
+              assets/card ; JPY ; -1000 ; 2026-01-05 // card
+                ---
+                receipt: 123
+                ---
+              expense     ;     ;
+        "};
+        let actual = super::format_content(
+            Arc::new(PathBuf::from("<test>")),
+            input.into(),
+            &Default::default(),
+            false,
+        )
+        .or_panic();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_wide_characters_are_aligned() {
+        let input = "2026-01-01\n  資産/現金;円;-1000\n  assets/\"my bank\";€;1000\n";
+        let expected = indoc::indoc! {r#"
+            2026-01-01
+              資産/現金        ; 円 ; -1000
+              assets/"my bank" ; €  ;  1000
+        "#};
+        let actual = super::format_content(
+            Arc::new(PathBuf::from("<test>")),
+            input.into(),
+            &Default::default(),
+            false,
+        )
+        .or_panic();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_amount_expressions() {
+        let input = "2026-01-01\n  a;;(1200*3)+450\n  b;;round (-1000/3) // split\n  c;;\n";
+        let expected = indoc::indoc! {"
+            2026-01-01
+              a ;  ; (1200 * 3) + 450
+              b ;  ; round(-1000 / 3) // split
+              c ;  ;
+        "};
+        let actual = super::format_content(
+            Arc::new(PathBuf::from("<test>")),
+            input.into(),
+            &Default::default(),
+            false,
+        )
+        .or_panic();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_variables() {
+        let input = "let rent=85000\nlet share =floor (rent / 3)\nset conversion_postings true\n\n2026-01-01\n  a;;-( rent+ 1)\n  b;;share * 2\n  c;;\n";
+        let expected = indoc::indoc! {"
+            let rent = 85000
+            let share = floor(rent / 3)
+            set conversion_postings true
+
+            2026-01-01
+              a ;  ; -(rent + 1)
+              b ;  ;   share * 2
+              c ;  ;
+        "};
+        let actual = super::format_content(
+            Arc::new(PathBuf::from("<test>")),
+            input.into(),
+            &Default::default(),
+            false,
+        )
+        .or_panic();
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest::rstest]
+    #[case::space_before_timestamp("1,000  2026-01-01\n", "1000 2026-01-01\n")]
+    #[case::no_space_after_timestamp("2026-01-01=\n", "2026-01-01=\n")]
+    #[case::no_space_before_percentage("2026-01-0110%\n", "2026-01-0110%\n")]
+    #[case::whitespace_after_indent("  \ta: b\n", "  a: b\n")]
+    fn test_misc_lines(#[case] input: &str, #[case] expected: &str) {
+        let actual = super::format_content(
+            Arc::new(PathBuf::from("<test>")),
+            input.into(),
+            &Default::default(),
+            false,
+        )
+        .or_panic();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_transaction_header() {
+        let input = "2026-01-01   *   Coffee shop   // latte\n  a;JPY;1\n  b;;\n";
+        let expected = "2026-01-01 * Coffee shop // latte\n  a ; JPY ; 1\n  b ;     ;\n";
+        let actual = super::format_content(
+            Arc::new(PathBuf::from("<test>")),
+            input.into(),
+            &Default::default(),
+            false,
+        )
+        .or_panic();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let diff = super::unified_diff(
+            std::path::Path::new("foo.boki"),
+            "2026-01-01\n  a;;1\n",
+            "2026-01-01\n  a ; ; 1\n",
+        );
+        assert_eq!(
+            diff,
+            "--- foo.boki\n+++ foo.boki\n@@ -1,2 +1,2 @@\n 2026-01-01\n-  a;;1\n+  a ; ; 1\n"
+        );
+    }
+
+    fn significant_tokens(content: &str) -> Vec<tokens::Token> {
+        lex::lex_string(content)
+            .expect("Failed to lex.")
+            .iter()
+            .map(|t| t.token().clone())
+            .filter(|t| !matches!(t, tokens::Token::Whitespace))
+            .collect()
+    }
+
+    fn timestamp_strategy() -> impl Strategy<Value = String> {
+        let date = (2000i32..2100, 1u32..=12, 1u32..=28)
+            .prop_map(|(y, m, d)| format!("{y:04}-{m:02}-{d:02}"));
+        let seconds =
+            prop::option::of((0u32..60, prop::option::of("[0-9]{1,3}"))).prop_map(|x| match x {
+                None => String::new(),
+                Some((s, None)) => format!(":{s:02}"),
+                Some((s, Some(frac))) => format!(":{s:02}.{frac}"),
+            });
+        let time = (0u32..24, 0u32..60, seconds)
+            .prop_map(|(h, m, seconds)| format!("{h:02}:{m:02}{seconds}"));
+        let offset = prop::option::of(prop_oneof![
+            Just("Z".to_string()),
+            (
+                prop::sample::select(vec!['+', '-']),
+                0u32..15,
+                prop::sample::select(vec![0u32, 30, 45]),
+            )
+                .prop_map(|(sign, h, m)| format!("{sign}{h:02}:{m:02}")),
+        ]);
+
+        (date, prop::option::of((time, offset))).prop_map(|(date, rest)| match rest {
+            None => date,
+            Some((time, offset)) => format!("{date} {time}{}", offset.unwrap_or_default()),
+        })
+    }
+
+    fn posting_strategy() -> impl Strategy<Value = String> {
+        let account = prop::collection::vec("[a-z][a-z0-9_]{0,8}", 1..4).prop_map(|x| x.join("/"));
+        let commodity = prop::option::of("[A-Z]{3}");
+        let amount = prop::option::of(-1_000_000i64..1_000_000);
+        let date = prop::option::of(timestamp_strategy());
+        let comment = prop::option::of("[a-zA-Z0-9 ]{0,16}");
+        let attributes = prop::option::of(0u32..1000);
+        let space = "[ ]{0,3}";
+
+        (account, commodity, amount, date, comment, attributes, space).prop_map(
+            |(account, commodity, amount, date, comment, attributes, space)| {
+                let mut line = format!(
+                    "  {account}{space};{space}{}{space};{space}{}",
+                    commodity.unwrap_or_default(),
+                    amount.map(|x| x.to_string()).unwrap_or_default()
+                );
+                if let Some(date) = date {
+                    line += &format!("{space};{space}{date}");
+                }
+                if let Some(comment) = comment {
+                    line += &format!(" //{comment}");
+                }
+                if let Some(receipt) = attributes {
+                    line += &format!("\n    ---\n    receipt: {receipt}\n    ---");
+                }
+                line
+            },
+        )
+    }
+
+    fn header_strategy() -> impl Strategy<Value = String> {
+        let status = prop::option::of(prop::sample::select(vec!["*", "!"]));
+        let description = prop::option::of("[a-zA-Z][a-zA-Z0-9 ]{0,16}");
+        let space = "[ ]{1,3}";
+
+        (timestamp_strategy(), status, description, space).prop_map(
+            |(ts, status, description, space)| {
+                let mut line = ts;
+                if let Some(status) = status {
+                    line += &format!("{space}{status}");
+                }
+                if let Some(description) = description {
+                    line += &format!("{space}{description}");
+                }
+                line
+            },
+        )
+    }
+
+    fn journal_strategy() -> impl Strategy<Value = String> {
+        let transaction = (
+            header_strategy(),
+            prop::collection::vec(posting_strategy(), 1..4),
+        )
+            .prop_map(|(header, postings)| format!("{header}\n{}\n", postings.join("\n")));
+        prop::collection::vec(transaction, 0..5).prop_map(|x| x.join("\n"))
+    }
+
+    fn style_strategy() -> impl Strategy<Value = config::FormatStyle> {
+        (
+            prop::sample::select(vec![
+                config::AlignmentScope::File,
+                config::AlignmentScope::Transaction,
+            ]),
+            any::<bool>(),
+            prop::sample::select(vec![
+                config::AmountAlignment::Left,
+                config::AmountAlignment::Right,
+            ]),
+            prop::sample::select(vec![
+                config::SignPlacement::Attached,
+                config::SignPlacement::Aligned,
+            ]),
+            2usize..6,
+            prop::sample::select(vec![config::YamlStyle::Block, config::YamlStyle::Flow]),
+        )
+            .prop_map(
+                |(
+                    alignment,
+                    thousands_separators,
+                    amount_alignment,
+                    sign_placement,
+                    indent_width,
+                    yaml_style,
+                )| {
+                    config::FormatStyle {
+                        alignment,
+                        thousands_separators,
+                        amount_alignment,
+                        sign_placement,
+                        indent_width,
+                        yaml_style,
+                    }
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip(content in journal_strategy(), style in style_strategy()) {
+            let filename = Arc::new(PathBuf::from("<test>"));
+            let formatted = super::format_content(filename, content.as_str().into(), &style, false).or_panic();
+
+            prop_assert_eq!(significant_tokens(&formatted), significant_tokens(&content));
+        }
+
+        #[test]
+        fn test_any_input(content in crate::cli::testing::journal_fragments()) {
+            let filename = Arc::new(PathBuf::from("<test>"));
+            let Ok(once) = super::format_content(filename.clone(), content.as_str().into(), &Default::default(), false) else {
+                return Ok(());
+            };
+            let twice = super::format_content(filename, once.as_str().into(), &Default::default(), false).or_panic();
+
+            prop_assert_eq!(significant_tokens(&once), significant_tokens(&content));
+            prop_assert_eq!(once, twice);
+        }
+
+        #[test]
+        fn test_idempotent(content in journal_strategy(), style in style_strategy()) {
+            let filename = Arc::new(PathBuf::from("<test>"));
+            let once = super::format_content(filename.clone(), content.as_str().into(), &style, false).or_panic();
+            let twice = super::format_content(filename, once.as_str().into(), &style, false).or_panic();
+
+            prop_assert_eq!(once, twice);
+        }
+    }
 }
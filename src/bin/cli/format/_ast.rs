@@ -1,11 +1,13 @@
-use boki::tokens;
+use boki::{ast, tokens};
 
 #[derive(Clone, Debug)]
 pub struct Posting {
     pub account: Vec<tokens::Token>,
     pub commodity: Option<String>,
-    pub amount: Option<i64>,
+    pub amount: Option<ast::Expression>,
+    pub date: Option<tokens::Timestamp>,
     pub comment: Option<String>,
+    pub attributes: Option<serde_yaml::Mapping>,
 }
 
 #[derive(Clone, Debug)]
@@ -0,0 +1,161 @@
+//! Formatter configuration, loaded from the `[format]` section of a `boki.toml` file.
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::error;
+
+pub const DEFAULT_CONFIG_FILENAME: &str = "boki.toml";
+
+/// Which postings are considered when aligning the account, commodity and amount columns.
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AlignmentScope {
+    /// All postings in the file share the same column widths.
+    #[default]
+    File,
+    /// Each transaction is aligned on its own.
+    Transaction,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AmountAlignment {
+    Left,
+    #[default]
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SignPlacement {
+    /// Only negative amounts have a sign, e.g. `-900` and `900`.
+    #[default]
+    Attached,
+    /// Amounts without a minus sign have a space in its place, e.g. `-900` and ` 900`,
+    /// so that the digits line up even when amounts are left-aligned.
+    Aligned,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum YamlStyle {
+    /// Regular, multi-line YAML.
+    #[default]
+    Block,
+    /// A single line of flow-style YAML, e.g. `{"foo": "bar"}`.
+    Flow,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FormatStyle {
+    pub alignment: AlignmentScope,
+    pub thousands_separators: bool,
+    pub amount_alignment: AmountAlignment,
+    pub sign_placement: SignPlacement,
+    pub indent_width: usize,
+    pub yaml_style: YamlStyle,
+}
+
+impl Default for FormatStyle {
+    fn default() -> Self {
+        Self {
+            alignment: AlignmentScope::default(),
+            thousands_separators: false,
+            amount_alignment: AmountAlignment::default(),
+            sign_placement: SignPlacement::default(),
+            indent_width: 2,
+            yaml_style: YamlStyle::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    format: FormatStyle,
+}
+
+fn parse_config(filename: Arc<PathBuf>, content: &str) -> Result<FormatStyle, Box<error::Error>> {
+    let config: ConfigFile = toml::from_str(content).map_err(|e| {
+        Box::new(error::Error::Config {
+            filename: filename.clone(),
+            message: e.to_string(),
+        })
+    })?;
+
+    // The lexer requires at least two spaces of indentation.
+    if config.format.indent_width < 2 {
+        return Err(Box::new(error::Error::Config {
+            filename,
+            message: "format.indent_width must be at least 2.".to_string(),
+        }));
+    }
+
+    Ok(config.format)
+}
+
+/// Loads the formatter style from `filename`.
+///
+/// If no filename is given, [DEFAULT_CONFIG_FILENAME] is used if it exists in the current directory,
+/// otherwise the default style is returned.
+pub fn load(filename: Option<&Path>) -> Result<FormatStyle, Box<error::Error>> {
+    let filename = match filename {
+        Some(x) => x,
+        None if Path::new(DEFAULT_CONFIG_FILENAME).exists() => Path::new(DEFAULT_CONFIG_FILENAME),
+        None => return Ok(FormatStyle::default()),
+    };
+    let filename = Arc::new(filename.to_path_buf());
+    let content = std::fs::read_to_string(filename.as_ref())
+        .map_err(error::map_io_error(filename.clone()))?;
+
+    parse_config(filename, &content)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(content: &str) -> Result<FormatStyle, Box<error::Error>> {
+        parse_config(Arc::new(PathBuf::from("boki.toml")), content)
+    }
+
+    #[test]
+    fn test_empty_config_is_default() {
+        assert_eq!(parse("").expect("Failed."), FormatStyle::default());
+    }
+
+    #[test]
+    fn test_all_options() {
+        let style = parse(indoc::indoc! {r#"
+            [format]
+            alignment = "transaction"
+            thousands_separators = true
+            amount_alignment = "left"
+            sign_placement = "aligned"
+            indent_width = 4
+            yaml_style = "flow"
+        "#})
+        .expect("Failed.");
+
+        assert_eq!(
+            style,
+            FormatStyle {
+                alignment: AlignmentScope::Transaction,
+                thousands_separators: true,
+                amount_alignment: AmountAlignment::Left,
+                sign_placement: SignPlacement::Aligned,
+                indent_width: 4,
+                yaml_style: YamlStyle::Flow,
+            }
+        );
+    }
+
+    #[rstest::rstest]
+    #[case::unknown_option("[format]\nfoo = 1")]
+    #[case::invalid_value("[format]\nalignment = \"page\"")]
+    #[case::indent_too_small("[format]\nindent_width = 1")]
+    fn test_rejects(#[case] content: &str) {
+        parse(content).expect_err("Should have failed.");
+    }
+}
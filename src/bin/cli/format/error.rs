@@ -1,15 +1,21 @@
 use boki::common_errors;
 use std::path::PathBuf;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub enum Error {
     IO {
-        filename: Rc<PathBuf>,
+        filename: Arc<PathBuf>,
         error: std::io::Error,
     },
     Lexer(common_errors::FileLexError),
     Parser(common_errors::FileParseError),
+    Config {
+        filename: Arc<PathBuf>,
+        message: String,
+    },
+    /// Returned by `--check` with the list of files which are not formatted.
+    Unformatted(Vec<PathBuf>),
 }
 
 impl From<common_errors::FileLexError> for Box<Error> {
@@ -29,11 +35,24 @@ impl crate::error::CLIError for Error {
         match &self {
             Self::Lexer(e) => format!("{e}"),
             Self::Parser(e) => format!("{e}"),
-            _ => todo!(),
+            Self::IO { filename, error } => format!("{}: {error}", filename.display()),
+            Self::Config { filename, message } => {
+                format!(
+                    "while reading configuration file: {}\n{message}",
+                    filename.to_string_lossy()
+                )
+            }
+            Self::Unformatted(filenames) => {
+                let mut s = "The following files are not formatted:".to_string();
+                for filename in filenames {
+                    s += &format!("\n  {}", filename.to_string_lossy());
+                }
+                s
+            }
         }
     }
 }
 
-pub fn map_io_error(filename: Rc<PathBuf>) -> impl FnOnce(std::io::Error) -> Box<Error> {
+pub fn map_io_error(filename: Arc<PathBuf>) -> impl FnOnce(std::io::Error) -> Box<Error> {
     move |error| Box::new(Error::IO { filename, error })
 }
@@ -1,5 +1,6 @@
 use super::_ast;
 use boki::{
+    ast,
     parsing::{self, Parser},
     tokens,
 };
@@ -52,9 +53,16 @@ fn parse_account(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<V
         let t = parsing::get_next(scanner)?;
         match &t {
             tokens::Token::Identifier(_) => parts.push(t.clone()),
+            tokens::Token::String(_) => parts.push(t.clone()),
             tokens::Token::AccountSeparator => parts.push(t.clone()),
+            // Whitespace right after the indent is only indentation.
+            tokens::Token::Whitespace if parts.is_empty() => {}
             tokens::Token::Whitespace => parts.push(t.clone()),
             tokens::Token::PostingSeparator => {
+                // Whitespace between the account and the separator is only alignment.
+                while matches!(parts.last(), Some(tokens::Token::Whitespace)) {
+                    parts.pop();
+                }
                 break;
             }
             _ => {
@@ -69,6 +77,40 @@ fn parse_account(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<V
     Ok(parts)
 }
 
+/// Parses the amount column. Whitespace is kept as a token before names, so it is dropped here.
+fn parse_amount(
+    scanner: &mut parsing::TokenScanner,
+) -> parsing::ParserResult<Option<ast::Expression>> {
+    let start = scanner.tell();
+    let mut column = vec![];
+    while let Some(t) = scanner.peek() {
+        match t {
+            tokens::Token::Whitespace => {}
+            tokens::Token::PostingSeparator
+            | tokens::Token::Comment(_)
+            | tokens::Token::LineSeparator
+            | tokens::Token::Eof => break,
+            _ => column.push(t.clone()),
+        }
+        scanner.advance(1)?;
+    }
+
+    if column.is_empty() {
+        return Ok(None);
+    }
+
+    let mut column_scanner = parsing::TokenScanner::from_slice(&column);
+    let amount = parsing::parse_expression(&mut column_scanner)?;
+    if column_scanner.peek().is_some() {
+        return Err(parsing::ParserError {
+            location: start,
+            details: parsing::ParserErrorDetails::Other("Not an amount.".to_string()),
+        });
+    }
+
+    Ok(Some(amount))
+}
+
 fn parse_posting(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<_ast::Node> {
     parsing::parse_indent(scanner)?;
 
@@ -79,19 +121,34 @@ fn parse_posting(scanner: &mut parsing::TokenScanner) -> parsing::ParserResult<_
     parsing::optional(parsing::parse_whitespace).parse(scanner)?;
     parsing::parse_posting_separator(scanner)?;
 
+    let amount = parse_amount(scanner)?;
+
     parsing::optional(parsing::parse_whitespace).parse(scanner)?;
-    let amount = parsing::optional(parsing::parse_amount).parse(scanner)?;
+    let date = parsing::optional(parsing::preceded(
+        parsing::parse_posting_separator,
+        parsing::parse_timestamp,
+    ))
+    .parse(scanner)?;
 
     parsing::optional(parsing::parse_whitespace).parse(scanner)?;
     let comment = parsing::optional(parsing::parse_comment).parse(scanner)?;
 
     parsing::parse_line_separator(scanner)?;
 
+    // YAML matter directly below a posting belongs to that posting.
+    let attributes = parsing::optional(parsing::terminated(
+        parsing::parse_yaml_matter,
+        parsing::parse_line_separator,
+    ))
+    .parse(scanner)?;
+
     Ok(_ast::Node::Posting(Box::new(_ast::Posting {
         account,
         commodity,
         amount,
+        date,
         comment,
+        attributes,
     })))
 }
 
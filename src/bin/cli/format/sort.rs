@@ -0,0 +1,299 @@
+//! This module handles reordering transactions chronologically.
+//!
+//! The file is split into chunks. A transaction chunk consists of the comment lines directly above
+//! the transaction header, the header itself and all indented lines below it (postings, YAML matter, comments).
+//! Everything else (e.g. `set` directives or standalone comments) is a fixed chunk, which acts as a barrier:
+//! transactions are only reordered between two fixed chunks, so that order-dependent directives keep
+//! applying to the same transactions.
+use super::_ast;
+use boki::tokens;
+
+enum Chunk {
+    Transaction {
+        timestamp: tokens::Timestamp,
+        body: Vec<_ast::Node>,
+        /// Blank lines following the transaction. These stay in place when transactions are reordered.
+        trailing: Vec<_ast::Node>,
+    },
+    Fixed(Vec<_ast::Node>),
+}
+
+enum LineKind {
+    Header(tokens::Timestamp),
+    Indented,
+    Blank,
+    Comment,
+    Other,
+}
+
+fn line_kind(node: &_ast::Node) -> LineKind {
+    let tokens = match node {
+        _ast::Node::Posting(_) => return LineKind::Indented,
+        _ast::Node::Misc(tokens) => tokens,
+    };
+
+    match tokens.first() {
+        Some(tokens::Token::Timestamp(ts)) => LineKind::Header(*ts),
+        Some(tokens::Token::Indent) | Some(tokens::Token::YamlMatter(_)) => LineKind::Indented,
+        Some(tokens::Token::LineSeparator) => LineKind::Blank,
+        Some(tokens::Token::Comment(_)) => LineKind::Comment,
+        _ => LineKind::Other,
+    }
+}
+
+fn split_chunks(nodes: Vec<_ast::Node>) -> Vec<Chunk> {
+    let mut chunks = vec![];
+    // Top-level comments which may belong to the next transaction.
+    let mut pending: Vec<_ast::Node> = vec![];
+
+    for node in nodes {
+        let kind = line_kind(&node);
+        let last = chunks.last_mut();
+        match (kind, last) {
+            (LineKind::Header(timestamp), _) => {
+                let mut body = std::mem::take(&mut pending);
+                body.push(node);
+                chunks.push(Chunk::Transaction {
+                    timestamp,
+                    body,
+                    trailing: vec![],
+                });
+            }
+            (LineKind::Comment, _) => pending.push(node),
+            (LineKind::Indented, Some(Chunk::Transaction { body, trailing, .. }))
+                if pending.is_empty() =>
+            {
+                // Blank lines between postings do not end the transaction.
+                body.append(trailing);
+                body.push(node)
+            }
+            (LineKind::Blank, Some(Chunk::Transaction { trailing, .. })) if pending.is_empty() => {
+                trailing.push(node)
+            }
+            (_, last) => {
+                pending.push(node);
+                match last {
+                    Some(Chunk::Fixed(nodes)) => nodes.append(&mut pending),
+                    _ => chunks.push(Chunk::Fixed(std::mem::take(&mut pending))),
+                }
+            }
+        }
+    }
+
+    if !pending.is_empty() {
+        chunks.push(Chunk::Fixed(pending));
+    }
+
+    chunks
+}
+
+/// Sorts a run of consecutive transactions by timestamp. The sort is stable, so transactions
+/// with the same timestamp keep their relative order.
+///
+/// Timestamps are compared by their local date and time as written, ignoring offsets. The formatter
+/// does not know the journal's timezone, so this is the only order that is independent of it.
+fn sort_run(run: &mut Vec<Chunk>, output: &mut Vec<_ast::Node>) {
+    let mut bodies = vec![];
+    let mut trailings = vec![];
+    for chunk in run.drain(..) {
+        if let Chunk::Transaction {
+            timestamp,
+            body,
+            trailing,
+        } = chunk
+        {
+            bodies.push((timestamp, body));
+            trailings.push(trailing);
+        }
+    }
+
+    bodies.sort_by_key(|(timestamp, _)| timestamp.naive_local());
+
+    for ((_, body), trailing) in bodies.into_iter().zip(trailings) {
+        output.extend(body);
+        output.extend(trailing);
+    }
+}
+
+pub(super) fn sort_transactions(nodes: Vec<_ast::Node>) -> Vec<_ast::Node> {
+    let mut output = vec![];
+    let mut run = vec![];
+
+    for chunk in split_chunks(nodes) {
+        match chunk {
+            Chunk::Transaction { .. } => run.push(chunk),
+            Chunk::Fixed(nodes) => {
+                sort_run(&mut run, &mut output);
+                output.extend(nodes);
+            }
+        }
+    }
+    sort_run(&mut run, &mut output);
+
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use crate::error::CLIErrorResult;
+
+    fn sort(content: &str) -> String {
+        super::super::format_content(
+            Arc::new(PathBuf::from("<test>")),
+            content.into(),
+            &Default::default(),
+            true,
+        )
+        .or_panic()
+    }
+
+    #[test]
+    fn test_sorts_transactions() {
+        let input = indoc::indoc! {"
+            2026-01-03
+              a ; JPY ; 3
+              b ;     ;
+
+            // The first transaction.
+            2026-01-01
+              ---
+              foo: bar
+              ---
+              a ; JPY ; 1 // one
+              b ;     ;
+
+            2026-01-02
+              a ; JPY ; 2
+              b ;     ;
+        "};
+        let expected = indoc::indoc! {"
+            // The first transaction.
+            2026-01-01
+              ---
+              foo: bar
+              ---
+              a ; JPY ; 1 // one
+              b ;     ;
+
+            2026-01-02
+              a ; JPY ; 2
+              b ;     ;
+
+            2026-01-03
+              a ; JPY ; 3
+              b ;     ;
+        "};
+
+        assert_eq!(sort(input), expected);
+    }
+
+    #[test]
+    fn test_does_not_move_transactions_across_set_directives() {
+        let input = indoc::indoc! {"
+            set default_commodity JPY
+
+            2026-01-02
+              a ; JPY ; 2
+              b ;     ;
+
+            2026-01-01
+              a ; JPY ; 1
+              b ;     ;
+
+            set default_commodity USD
+
+            2025-01-01
+              a ; JPY ; 0
+              b ;     ;
+        "};
+        let expected = indoc::indoc! {"
+            set default_commodity JPY
+
+            2026-01-01
+              a ; JPY ; 1
+              b ;     ;
+
+            2026-01-02
+              a ; JPY ; 2
+              b ;     ;
+
+            set default_commodity USD
+
+            2025-01-01
+              a ; JPY ; 0
+              b ;     ;
+        "};
+
+        assert_eq!(sort(input), expected);
+    }
+
+    #[test]
+    fn test_standalone_comments_stay_in_place() {
+        let input = indoc::indoc! {"
+            // Standalone comment.
+
+            2026-01-02
+              a ; JPY ; 2
+              b ;     ;
+
+            2026-01-01
+              a ; JPY ; 1
+              b ;     ;
+        "};
+        let expected = indoc::indoc! {"
+            // Standalone comment.
+
+            2026-01-01
+              a ; JPY ; 1
+              b ;     ;
+
+            2026-01-02
+              a ; JPY ; 2
+              b ;     ;
+        "};
+
+        assert_eq!(sort(input), expected);
+    }
+
+    #[test]
+    fn test_sorts_by_time_of_day() {
+        let input = indoc::indoc! {"
+            2026-01-01 12:00
+              a ; JPY ; 2
+              b ;     ;
+
+            2026-01-01
+              a ; JPY ; 1
+              b ;     ;
+        "};
+        let expected = indoc::indoc! {"
+            2026-01-01
+              a ; JPY ; 1
+              b ;     ;
+
+            2026-01-01 12:00
+              a ; JPY ; 2
+              b ;     ;
+        "};
+
+        assert_eq!(sort(input), expected);
+    }
+
+    #[test]
+    fn test_same_timestamp_keeps_order() {
+        let input = indoc::indoc! {"
+            2026-01-01
+              a ; JPY ; 1
+              b ;     ;
+
+            2026-01-01
+              a ; JPY ; 2
+              b ;     ;
+        "};
+
+        assert_eq!(sort(input), input);
+    }
+}
@@ -1,23 +1,37 @@
 //! This module handles converting the formatter's AST into text that further be written to a file.
 use super::_ast;
-use boki::tokens;
+use super::config::{AlignmentScope, AmountAlignment, FormatStyle, SignPlacement, YamlStyle};
+use boki::{ast, tokens};
 
 #[derive(Clone, Debug)]
 pub struct FormatContext {
+    style: FormatStyle,
     account_column_width: usize,
     commodity_column_width: usize,
     amount_column_width: usize,
+    date_column_width: usize,
 }
 
-#[allow(clippy::derivable_impls)]
-impl Default for FormatContext {
-    fn default() -> Self {
+impl FormatContext {
+    fn new(style: FormatStyle) -> Self {
         Self {
+            style,
             account_column_width: 0,
             commodity_column_width: 0,
             amount_column_width: 0,
+            date_column_width: 0,
         }
     }
+
+    fn indent(&self) -> String {
+        " ".repeat(self.style.indent_width)
+    }
+}
+
+impl Default for FormatContext {
+    fn default() -> Self {
+        Self::new(FormatStyle::default())
+    }
 }
 
 /// Type wrapper with a [std::fmt::Display] derivation. We implement the derivation on the wrapper,
@@ -55,36 +69,155 @@ impl std::fmt::Display for ToText<&tokens::Token> {
                 "{}",
                 match kw {
                     tokens::Keyword::Set => "set",
+                    tokens::Keyword::Auto => "auto",
+                    tokens::Keyword::Budget => "budget",
+                    tokens::Keyword::Account => "account",
+                    tokens::Keyword::With => "with",
+                    tokens::Keyword::End => "end",
+                    tokens::Keyword::Let => "let",
                 }
             ),
-            tokens::Token::Timestamp(ts) => {
-                if ts.time() == chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap() {
-                    write!(f, "{}", ts.date_naive())?;
-                }
-
-                Ok(())
-            }
+            tokens::Token::Timestamp(ts) => write!(f, "{}", ts),
+            // The lexer folds the whitespace before these into the token, so we have to write it back.
+            tokens::Token::Status(x) => write!(f, " {}", x),
+            tokens::Token::Description(x) => write!(f, " {}", x),
             tokens::Token::Amount(amt) => write!(f, "{}", amt),
+            tokens::Token::Percentage(x) => write!(f, "{}", format_percentage(*x)),
+            tokens::Token::Operator(x) => write!(f, "{}", x),
+            tokens::Token::LeftParenthesis => write!(f, "("),
+            tokens::Token::RightParenthesis => write!(f, ")"),
             tokens::Token::YamlMatter(mapping) => {
-                writeln!(f, "  ---")?;
-                let mut s = serde_yaml::to_string(mapping).unwrap();
-                s = s.replace("\n", "\n  ");
-                write!(f, "  {}", s)?;
-                write!(f, "---")?;
-                Ok(())
+                write_yaml(f, mapping, &self.0.indent(), self.0.style.yaml_style)
             }
             tokens::Token::LineSeparator => writeln!(f),
             tokens::Token::AccountSeparator => write!(f, "/"),
             tokens::Token::PostingSeparator => write!(f, ";"),
+            tokens::Token::Equals => write!(f, "="),
             tokens::Token::Identifier(x) => write!(f, "{}", x),
+            tokens::Token::String(x) => {
+                write!(f, "\"{}\"", x.replace('\\', "\\\\").replace('"', "\\\""))
+            }
             tokens::Token::Comment(x) => write!(f, "//{}", x),
             tokens::Token::Whitespace => write!(f, " "),
-            tokens::Token::Indent => write!(f, "  "),
+            tokens::Token::Indent => write!(f, "{}", self.0.indent()),
             tokens::Token::Eof => Ok(()),
         }
     }
 }
 
+fn format_yaml(mapping: &serde_yaml::Mapping, style: YamlStyle) -> String {
+    if style == YamlStyle::Flow {
+        // JSON is valid flow-style YAML. However, not every YAML mapping can be represented as JSON
+        // (e.g. non-string keys), so we fall back to block style if anything would get lost.
+        if let Ok(s) = serde_json::to_string(mapping) {
+            if serde_yaml::from_str::<serde_yaml::Mapping>(&s).is_ok_and(|x| &x == mapping) {
+                return s;
+            }
+        }
+    }
+
+    serde_yaml::to_string(mapping).unwrap()
+}
+
+fn write_yaml(
+    f: &mut std::fmt::Formatter<'_>,
+    mapping: &serde_yaml::Mapping,
+    indent: &str,
+    style: YamlStyle,
+) -> std::fmt::Result {
+    writeln!(f, "{indent}---")?;
+    for line in format_yaml(mapping, style).lines() {
+        writeln!(f, "{indent}{line}")?;
+    }
+    write!(f, "{indent}---")
+}
+
+fn format_amount(amount: i64, style: &FormatStyle) -> String {
+    let digits = amount.unsigned_abs().to_string();
+    let digits = if style.thousands_separators {
+        let mut groups: Vec<&str> = vec![];
+        let mut end = digits.len();
+        while end > 3 {
+            groups.push(&digits[end - 3..end]);
+            end -= 3;
+        }
+        groups.push(&digits[..end]);
+        groups.reverse();
+        groups.join(",")
+    } else {
+        digits
+    };
+
+    if amount < 0 {
+        format!("-{digits}")
+    } else {
+        digits
+    }
+}
+
+/// Writes an amount expression with a single space around each binary operator, e.g. `(1200 * 3) + 450`.
+fn format_expression(expression: &ast::Expression, style: &FormatStyle) -> String {
+    match expression {
+        ast::Expression::Amount(x) => format_amount(*x, style),
+        ast::Expression::Variable(x) => x.clone(),
+        ast::Expression::Parenthesized(x) => format!("({})", format_expression(x, style)),
+        ast::Expression::Negation(x) => format!("-{}", format_expression(x, style)),
+        ast::Expression::Binary(l, operator, r) => format!(
+            "{} {operator} {}",
+            format_expression(l, style),
+            format_expression(r, style)
+        ),
+        ast::Expression::Rounded(rounding, x) => {
+            format!("{rounding}({})", format_expression(x, style))
+        }
+    }
+}
+
+/// Writes a percentage given in basis points, without trailing zeros (e.g. `2.5%`).
+fn format_percentage(basis_points: i64) -> String {
+    let sign = if basis_points < 0 { "-" } else { "" };
+    let abs = basis_points.unsigned_abs();
+    let fraction = format!("{:02}", abs % 100);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        format!("{sign}{}%", abs / 100)
+    } else {
+        format!("{sign}{}.{fraction}%", abs / 100)
+    }
+}
+
+/// Pads `s` with spaces to the given display width, so that columns line up even with wide characters.
+fn pad_right(s: &str, width: usize) -> String {
+    let padding = width.saturating_sub(boki::utils::display_width(s));
+    format!("{s}{}", " ".repeat(padding))
+}
+
+/// Pads `s` with spaces on the left to the given display width.
+fn pad_left(s: &str, width: usize) -> String {
+    let padding = width.saturating_sub(boki::utils::display_width(s));
+    format!("{}{s}", " ".repeat(padding))
+}
+
+/// Writes an amount for the amount column. With an aligned sign, a plain amount without a minus sign
+/// is written with a space in its place, so that the digits line up even when amounts are left-aligned.
+/// An expression reads as a whole, so it is written as is.
+fn format_column_amount(amount: &ast::Expression, style: &FormatStyle) -> String {
+    let s = format_expression(amount, style);
+    match (style.sign_placement, amount) {
+        (SignPlacement::Aligned, ast::Expression::Amount(x)) if *x >= 0 => format!(" {s}"),
+        _ => s,
+    }
+}
+
+/// Pads an amount to the given width, according to the style.
+fn align_amount(amount: &ast::Expression, width: usize, style: &FormatStyle) -> String {
+    let s = format_column_amount(amount, style);
+    match style.amount_alignment {
+        AmountAlignment::Left => pad_right(&s, width),
+        AmountAlignment::Right => pad_left(&s, width),
+    }
+}
+
 fn fold_tokens(tokens: &[tokens::Token]) -> Vec<tokens::Token> {
     tokens.iter().fold(vec![], |mut a, t| {
         match (a.last(), t) {
@@ -105,44 +238,119 @@ fn fold_tokens(tokens: &[tokens::Token]) -> Vec<tokens::Token> {
     })
 }
 
+/// Writes the tokens of a line which is not a posting. The lexer folds the whitespace before
+/// amounts, percentages, timestamps and operators into the tokens themselves, so we have to write it back.
+fn write_misc(
+    f: &mut std::fmt::Formatter<'_>,
+    ctx: &FormatContext,
+    line: &[tokens::Token],
+) -> std::fmt::Result {
+    use tokens::Token;
+
+    let mut previous: Option<&Token> = None;
+    let mut after_operand = false;
+    let mut after_binary = false;
+    for t in line {
+        // The indent already stands for all of the leading whitespace.
+        if t == &Token::Whitespace && previous == Some(&Token::Indent) {
+            continue;
+        }
+
+        let space = match previous {
+            None | Some(Token::Whitespace | Token::Indent | Token::LineSeparator) => false,
+            // Anything after a space after a timestamp would be a description.
+            Some(Token::Timestamp(_)) => false,
+            Some(Token::LeftParenthesis) => false,
+            // A rounding function, e.g. `round(rent / 3)`.
+            Some(Token::Identifier(_)) if t == &Token::LeftParenthesis => false,
+            Some(Token::Operator(_) | Token::Equals) => {
+                after_binary
+                    && matches!(
+                        t,
+                        Token::Amount(_)
+                            | Token::Identifier(_)
+                            | Token::LeftParenthesis
+                            | Token::Operator(_)
+                    )
+            }
+            Some(_) => matches!(
+                t,
+                Token::Amount(_)
+                    | Token::Percentage(_)
+                    | Token::Timestamp(_)
+                    | Token::LeftParenthesis
+                    | Token::Operator(_)
+                    | Token::Equals
+            ),
+        };
+        if space {
+            write!(f, " ")?;
+        }
+        write!(f, "{}", ToText::new(ctx.clone(), t))?;
+
+        after_binary = (matches!(t, Token::Operator(_)) && after_operand) || t == &Token::Equals;
+        after_operand = matches!(
+            t,
+            Token::Amount(_) | Token::RightParenthesis | Token::Identifier(_)
+        );
+        previous = Some(t);
+    }
+
+    Ok(())
+}
+
 impl std::fmt::Display for ToText<&_ast::Posting> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.with_context(&tokens::Token::Indent))?;
         write!(
             f,
-            "{: <width$} ",
-            format!("{}", self.with_context(self.1.account.as_slice())),
-            width = self.0.account_column_width
+            "{} ",
+            pad_right(
+                &format!("{}", self.with_context(self.1.account.as_slice())),
+                self.0.account_column_width
+            )
         )?;
         write!(f, "{}", self.with_context(&tokens::Token::PostingSeparator))?;
         write!(
             f,
-            " {: <width$} ",
-            format!("{}", self.1.commodity.clone().unwrap_or("".to_string())),
-            width = self.0.commodity_column_width
+            " {} ",
+            pad_right(
+                self.1.commodity.as_deref().unwrap_or(""),
+                self.0.commodity_column_width
+            )
         )?;
         write!(f, "{}", self.with_context(&tokens::Token::PostingSeparator))?;
 
-        if self.1.amount.is_some() || self.1.comment.is_some() {
-            write!(
-                f,
-                "{: >width$}",
-                format!(
-                    "{}",
-                    self.1
-                        .amount
-                        .map(|x| x.to_string())
-                        .unwrap_or("".to_string())
-                ),
-                width = self.0.amount_column_width + 1
-            )?;
-            if let Some(comment) = &self.1.comment {
-                write!(f, " //{}", comment)?;
+        if self.1.amount.is_some() || self.1.date.is_some() || self.1.comment.is_some() {
+            let amount = self
+                .1
+                .amount
+                .as_ref()
+                .map(|x| align_amount(x, self.0.amount_column_width, &self.0.style))
+                .unwrap_or(" ".repeat(self.0.amount_column_width));
+            let mut rest = format!(" {amount}");
+            if let Some(date) = &self.1.date {
+                rest += &format!(
+                    " ; {: <width$}",
+                    date.to_string(),
+                    width = self.0.date_column_width
+                );
+            }
+            match &self.1.comment {
+                Some(comment) => write!(f, "{rest} //{comment}")?,
+                None => write!(f, "{}", rest.trim_end())?,
             }
         }
 
         write!(f, "{}", self.with_context(&tokens::Token::LineSeparator))?;
 
+        if let Some(attributes) = &self.1.attributes {
+            // Posting attributes are indented one level deeper than the posting itself.
+            let indent = self.0.indent().repeat(2);
+            write_yaml(f, attributes, &indent, self.0.style.yaml_style)?;
+            write!(f, "{}", self.with_context(&tokens::Token::LineSeparator))?;
+        }
+
         Ok(())
     }
 }
@@ -151,7 +359,7 @@ impl std::fmt::Display for ToText<&_ast::Node> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.1 {
             _ast::Node::Misc(tokens) => {
-                write!(f, "{}", self.with_context(fold_tokens(tokens).as_slice()))?;
+                write_misc(f, &self.0, &fold_tokens(tokens))?;
             }
             _ast::Node::Posting(posting) => {
                 write!(f, "{}", self.with_context(posting.as_ref()))?;
@@ -162,8 +370,8 @@ impl std::fmt::Display for ToText<&_ast::Node> {
     }
 }
 
-fn compute_format(nodes: &[_ast::Node]) -> FormatContext {
-    let mut ctx = FormatContext::default();
+fn compute_format(nodes: &[_ast::Node], style: &FormatStyle) -> FormatContext {
+    let mut ctx = FormatContext::new(style.clone());
 
     for node in nodes {
         #[allow(clippy::single_match)]
@@ -171,20 +379,26 @@ fn compute_format(nodes: &[_ast::Node]) -> FormatContext {
             _ast::Node::Posting(posting) => {
                 let acct_string =
                     format!("{}", ToText::new(ctx.clone(), posting.account.as_slice()));
-                ctx.account_column_width =
-                    std::cmp::max(ctx.account_column_width, acct_string.len());
+                ctx.account_column_width = std::cmp::max(
+                    ctx.account_column_width,
+                    boki::utils::display_width(&acct_string),
+                );
 
                 ctx.commodity_column_width = std::cmp::max(
                     ctx.commodity_column_width,
-                    posting.commodity.clone().unwrap_or("".to_string()).len(),
+                    boki::utils::display_width(posting.commodity.as_deref().unwrap_or("")),
                 );
                 ctx.amount_column_width = std::cmp::max(
                     ctx.amount_column_width,
                     posting
                         .amount
-                        .map(|x| x.to_string())
-                        .unwrap_or("".to_string())
-                        .len(),
+                        .as_ref()
+                        .map(|x| boki::utils::display_width(&format_column_amount(x, style)))
+                        .unwrap_or(0),
+                );
+                ctx.date_column_width = std::cmp::max(
+                    ctx.date_column_width,
+                    posting.date.map(|x| x.to_string().len()).unwrap_or(0),
                 );
             }
             _ => {}
@@ -194,10 +408,175 @@ fn compute_format(nodes: &[_ast::Node]) -> FormatContext {
     ctx
 }
 
-pub(super) fn to_displayable(nodes: &[_ast::Node]) -> impl std::fmt::Display + '_ {
-    let ctx = compute_format(nodes);
-    ToText::new(ctx, nodes)
+/// Returns whether the node is part of the same block as the previous node,
+/// i.e. whether it is indented.
+fn continues_block(node: &_ast::Node) -> bool {
+    match node {
+        _ast::Node::Posting(_) => true,
+        _ast::Node::Misc(tokens) => matches!(
+            tokens.first(),
+            Some(tokens::Token::Indent) | Some(tokens::Token::YamlMatter(_))
+        ),
+    }
+}
+
+/// Splits the nodes into groups which are aligned together.
+fn split_alignment_groups<'a>(
+    nodes: &'a [_ast::Node],
+    style: &FormatStyle,
+) -> Vec<&'a [_ast::Node]> {
+    match style.alignment {
+        AlignmentScope::File => vec![nodes],
+        AlignmentScope::Transaction => {
+            let mut groups = vec![];
+            let mut start = 0;
+            for (i, node) in nodes.iter().enumerate() {
+                if i > start && !continues_block(node) {
+                    groups.push(&nodes[start..i]);
+                    start = i;
+                }
+            }
+            groups.push(&nodes[start..]);
+            groups
+        }
+    }
+}
+
+struct Document<'a>(Vec<ToText<&'a [_ast::Node]>>);
+
+impl std::fmt::Display for Document<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for group in self.0.iter() {
+            write!(f, "{group}")?;
+        }
+
+        Ok(())
+    }
+}
+
+pub(super) fn to_displayable<'a>(
+    nodes: &'a [_ast::Node],
+    style: &FormatStyle,
+) -> impl std::fmt::Display + 'a {
+    Document(
+        split_alignment_groups(nodes, style)
+            .into_iter()
+            .map(|group| ToText::new(compute_format(group, style), group))
+            .collect(),
+    )
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use super::*;
+
+    #[rstest::rstest]
+    #[case::date("2026-01-01", "2026-01-01")]
+    #[case::hours_minutes("2026-01-01 12:30", "2026-01-01 12:30")]
+    #[case::zero_seconds("2026-01-01 12:30:00.000", "2026-01-01 12:30")]
+    #[case::seconds("2026-01-01 12:30:45", "2026-01-01 12:30:45")]
+    #[case::milliseconds("2026-01-01 12:30:45.120-05:30", "2026-01-01 12:30:45.120-05:30")]
+    #[case::midnight_with_offset("2026-01-01 00:00:00.000+09:00", "2026-01-01 00:00+09:00")]
+    #[case::zulu("2026-01-01 12:30Z", "2026-01-01 12:30+00:00")]
+    fn test_timestamp(#[case] timestamp: &str, #[case] expected: &str) {
+        let token = boki::lex::lex_string(timestamp).expect("Invalid test case.")[0]
+            .token()
+            .clone();
+        let actual = format!("{}", ToText::new(FormatContext::default(), &token));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_string() {
+        let token = tokens::Token::String(r#"a "b" \ c"#.into());
+        let actual = format!("{}", ToText::new(FormatContext::default(), &token));
+        assert_eq!(actual, r#""a \"b\" \\ c""#);
+    }
+
+    #[rstest::rstest]
+    #[case::integer(1000, "10%")]
+    #[case::fraction(250, "2.5%")]
+    #[case::negative(-1005, "-10.05%")]
+    fn test_percentage(#[case] basis_points: i64, #[case] expected: &str) {
+        assert_eq!(format_percentage(basis_points), expected);
+    }
+
+    #[rstest::rstest]
+    #[case::plain(-1234567, 10, false, AmountAlignment::Right, SignPlacement::Attached, "  -1234567")]
+    #[case::thousands(-1234567, 10, true, AmountAlignment::Right, SignPlacement::Attached, "-1,234,567")]
+    #[case::thousands_short(123, 5, true, AmountAlignment::Right, SignPlacement::Attached, "  123")]
+    #[case::thousands_exact(
+        123456,
+        7,
+        true,
+        AmountAlignment::Right,
+        SignPlacement::Attached,
+        "123,456"
+    )]
+    #[case::left(-900, 6, false, AmountAlignment::Left, SignPlacement::Attached, "-900  ")]
+    #[case::sign_aligned(-900, 6, false, AmountAlignment::Left, SignPlacement::Aligned, "-900  ")]
+    #[case::sign_aligned_positive(
+        900,
+        6,
+        false,
+        AmountAlignment::Left,
+        SignPlacement::Aligned,
+        " 900  "
+    )]
+    #[case::sign_aligned_right(
+        900,
+        6,
+        false,
+        AmountAlignment::Right,
+        SignPlacement::Aligned,
+        "   900"
+    )]
+    fn test_align_amount(
+        #[case] amount: i64,
+        #[case] width: usize,
+        #[case] thousands_separators: bool,
+        #[case] amount_alignment: AmountAlignment,
+        #[case] sign_placement: SignPlacement,
+        #[case] expected: &str,
+    ) {
+        let style = FormatStyle {
+            thousands_separators,
+            amount_alignment,
+            sign_placement,
+            ..FormatStyle::default()
+        };
+        assert_eq!(align_amount(&amount.into(), width, &style), expected);
+    }
+
+    #[rstest::rstest]
+    #[case::right(AmountAlignment::Right, "  家賃")]
+    #[case::left(AmountAlignment::Left, "家賃  ")]
+    fn test_align_amount_with_wide_characters(
+        #[case] amount_alignment: AmountAlignment,
+        #[case] expected: &str,
+    ) {
+        let style = FormatStyle {
+            amount_alignment,
+            ..FormatStyle::default()
+        };
+        let amount = ast::Expression::Variable("家賃".to_string());
+        assert_eq!(align_amount(&amount, 6, &style), expected);
+    }
+
+    #[test]
+    fn test_flow_yaml() {
+        let mapping: serde_yaml::Mapping =
+            serde_yaml::from_str("foo: bar\nbaz: [1, 2]").expect("Invalid test case.");
+        assert_eq!(
+            format_yaml(&mapping, YamlStyle::Flow),
+            r#"{"foo":"bar","baz":[1,2]}"#
+        );
+    }
+
+    #[test]
+    fn test_flow_yaml_falls_back_to_block() {
+        let mapping: serde_yaml::Mapping =
+            serde_yaml::from_str("1: bar").expect("Invalid test case.");
+        assert_eq!(format_yaml(&mapping, YamlStyle::Flow), "1: bar\n");
+    }
+}
@@ -0,0 +1,121 @@
+//! The `boki is` report, which shows the income and expenses of each period.
+use std::collections::BTreeMap;
+
+use boki::output::{self, AccountType};
+
+use super::export::{self, Result};
+use super::report::{self, Period, Table};
+
+fn render(journal: &output::Journal, period: Period) -> Result<Table> {
+    let starts = report::periods(journal, period);
+    let mut table = report::period_table(period, &starts);
+
+    let income = report::compute_balances(journal, &starts, &[AccountType::Income], false)?;
+    let expense = report::compute_balances(journal, &starts, &[AccountType::Expense], false)?;
+
+    let income_totals = report::add_section(&mut table, "income", &income, -1)?;
+    let expense_totals = report::add_section(&mut table, "expense", &expense, 1)?;
+
+    let mut net_income: BTreeMap<&String, Vec<i64>> = BTreeMap::new();
+    for (totals, sign) in [(&income_totals, 1), (&expense_totals, -1)] {
+        for (commodity, amounts) in totals {
+            let net = net_income
+                .entry(commodity)
+                .or_insert_with(|| vec![0; starts.len()]);
+            for (x, y) in net.iter_mut().zip(amounts) {
+                *x = y
+                    .checked_mul(sign)
+                    .and_then(|y| x.checked_add(y))
+                    .ok_or_else(|| {
+                        export::error::out_of_range(format!("the net income in {commodity}"))
+                    })?;
+            }
+        }
+    }
+
+    for (commodity, amounts) in net_income {
+        let mut row = vec!["net income".to_string(), commodity.clone()];
+        row.extend(amounts.iter().map(|x| x.to_string()));
+        table.push(row);
+    }
+
+    Ok(table)
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    pub journal: export::JournalArgs,
+
+    #[arg(short, long, value_enum, default_value_t = Period::Monthly)]
+    period: Period,
+}
+
+pub fn run(args: &Args) -> Result<()> {
+    let journal = args.journal.compile()?;
+    print!("{}", render(&journal, args.period)?);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cli::testing::transaction;
+
+    #[test]
+    fn test_render() {
+        let mut journal = output::Journal {
+            transactions: vec![
+                transaction(
+                    "2026-01-25T00:00:00+09:00",
+                    &[("income/salary", -300000), ("assets/bank", 300000)],
+                ),
+                transaction(
+                    "2026-02-03T00:00:00+09:00",
+                    &[
+                        ("expense/food", 40000),
+                        ("cost/rent", 80000),
+                        ("assets/bank", -120000),
+                    ],
+                ),
+            ],
+            ..Default::default()
+        };
+        journal
+            .accounts
+            .insert("cost".to_string(), AccountType::Expense);
+
+        assert_eq!(
+            render(&journal, Period::Monthly)
+                .expect("Failed.")
+                .to_string(),
+            [
+                "account          commodity  2026-01  2026-02",
+                "income",
+                "  income/salary  JPY         300000        0",
+                "total income     JPY         300000        0",
+                "expense",
+                "  cost/rent      JPY              0    80000",
+                "  expense/food   JPY              0    40000",
+                "total expense    JPY              0   120000",
+                "net income       JPY         300000  -120000",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_render_out_of_range() {
+        let journal = output::Journal {
+            transactions: vec![transaction(
+                "2026-01-01T00:00:00+09:00",
+                &[("income/salary", -i64::MAX), ("expense/food", -2)],
+            )],
+            ..Default::default()
+        };
+
+        render(&journal, Period::Monthly).expect_err("Should have failed.");
+    }
+}
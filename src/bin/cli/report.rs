@@ -0,0 +1,385 @@
+//! Helpers shared by the reports, which are computed from a compiled journal.
+use std::collections::BTreeMap;
+
+use boki::{output, utils};
+use chrono::{Datelike, NaiveDate};
+
+use super::export::{error, Result};
+
+/// The length of the periods a report is split into.
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum Period {
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl Period {
+    /// Returns the first day of the period which contains `date`.
+    pub fn start(self, date: NaiveDate) -> NaiveDate {
+        let month = match self {
+            Period::Monthly => date.month(),
+            Period::Quarterly => (date.month() - 1) / 3 * 3 + 1,
+            Period::Yearly => 1,
+        };
+        NaiveDate::from_ymd_opt(date.year(), month, 1).unwrap()
+    }
+
+    /// Returns the first day of the period after the one starting at `start`.
+    pub fn next(self, start: NaiveDate) -> NaiveDate {
+        let months = match self {
+            Period::Monthly => 1,
+            Period::Quarterly => 3,
+            Period::Yearly => 12,
+        };
+        start + chrono::Months::new(months)
+    }
+
+    /// Returns the name of the period starting at `start`, e.g. `2026-01`, `2026-Q1` or `2026`.
+    pub fn name(self, start: NaiveDate) -> String {
+        match self {
+            Period::Monthly => start.format("%Y-%m").to_string(),
+            Period::Quarterly => format!("{}-Q{}", start.year(), start.month0() / 3 + 1),
+            Period::Yearly => start.year().to_string(),
+        }
+    }
+}
+
+impl From<output::BudgetPeriod> for Period {
+    fn from(value: output::BudgetPeriod) -> Self {
+        match value {
+            output::BudgetPeriod::Monthly => Period::Monthly,
+            output::BudgetPeriod::Quarterly => Period::Quarterly,
+            output::BudgetPeriod::Yearly => Period::Yearly,
+        }
+    }
+}
+
+/// Returns the local date of a transaction, i.e. in the offset it was recorded in.
+pub fn transaction_date(t: &output::Transaction) -> NaiveDate {
+    t.header.timestamp.date_naive()
+}
+
+/// Returns the start of every period between the first and the last transaction.
+pub fn periods(journal: &output::Journal, period: Period) -> Vec<NaiveDate> {
+    let dates = journal.transactions.iter().map(transaction_date);
+    let (Some(first), Some(last)) = (dates.clone().min(), dates.max()) else {
+        return vec![];
+    };
+
+    let mut starts = vec![];
+    let mut start = period.start(first);
+    while start <= last {
+        starts.push(start);
+        start = period.next(start);
+    }
+
+    starts
+}
+
+/// Returns the type of an account. Declared types take precedence (the most specific declaration wins),
+/// otherwise the type is derived from the root of the account, e.g. `expense/food` is an expense.
+pub fn classify(journal: &output::Journal, account: &str) -> Option<output::AccountType> {
+    let declared = journal
+        .accounts
+        .iter()
+        .filter(|(k, _)| utils::is_same_or_subaccount(k, account))
+        .max_by_key(|(k, _)| k.len())
+        .map(|(_, v)| *v);
+    if declared.is_some() {
+        return declared;
+    }
+
+    match account.split('/').next() {
+        Some("assets") => Some(output::AccountType::Assets),
+        Some("liabilities") => Some(output::AccountType::Liabilities),
+        Some("equity") => Some(output::AccountType::Equity),
+        Some("income") => Some(output::AccountType::Income),
+        Some("expense") => Some(output::AccountType::Expense),
+        _ => None,
+    }
+}
+
+/// Amounts per account and commodity, with one entry for each period of a report.
+pub type Balances = BTreeMap<(String, String), Vec<i64>>;
+
+/// Sums up the postings to accounts of the given types in each period starting at one of `starts`.
+///
+/// If `cumulative` is true, each entry is the balance at the end of its period instead,
+/// which includes everything posted before it as well.
+pub fn compute_balances(
+    journal: &output::Journal,
+    starts: &[NaiveDate],
+    account_types: &[output::AccountType],
+    cumulative: bool,
+) -> Result<Balances> {
+    let mut balances = Balances::new();
+
+    for t in &journal.transactions {
+        let date = transaction_date(t);
+        let Some(i) = starts.iter().rposition(|start| *start <= date) else {
+            continue;
+        };
+
+        for p in &t.postings {
+            if !classify(journal, &p.account).is_some_and(|x| account_types.contains(&x)) {
+                continue;
+            }
+
+            let amounts = balances
+                .entry((p.account.clone(), p.commodity.clone()))
+                .or_insert_with(|| vec![0; starts.len()]);
+            amounts[i] = amounts[i].checked_add(p.amount).ok_or_else(|| {
+                error::out_of_range(format!("the balance of {} in {}", p.account, p.commodity))
+            })?;
+        }
+    }
+
+    if cumulative {
+        for ((account, commodity), amounts) in balances.iter_mut() {
+            for i in 1..amounts.len() {
+                amounts[i] = amounts[i].checked_add(amounts[i - 1]).ok_or_else(|| {
+                    error::out_of_range(format!("the balance of {account} in {commodity}"))
+                })?;
+            }
+        }
+    }
+
+    Ok(balances)
+}
+
+/// Creates a table with a column for the account, the commodity and each period starting at one of `starts`.
+pub fn period_table(period: Period, starts: &[NaiveDate]) -> Table {
+    let mut columns = vec![
+        ("account".to_string(), Align::Left),
+        ("commodity".to_string(), Align::Left),
+    ];
+    columns.extend(starts.iter().map(|x| (period.name(*x), Align::Right)));
+    Table::new(&columns)
+}
+
+/// Adds a section of accounts to a report, followed by its totals per commodity, and returns those totals.
+///
+/// Credit-normal sections (e.g. income) should be given a `sign` of -1, so that they are shown as positive.
+pub fn add_section(
+    table: &mut Table,
+    name: &str,
+    balances: &Balances,
+    sign: i64,
+) -> Result<BTreeMap<String, Vec<i64>>> {
+    let mut header = vec![name.to_string()];
+    header.resize(table.columns.len(), "".to_string());
+    table.push(header);
+
+    let mut totals: BTreeMap<String, Vec<i64>> = BTreeMap::new();
+    for ((account, commodity), amounts) in balances {
+        let total = totals
+            .entry(commodity.clone())
+            .or_insert_with(|| vec![0; amounts.len()]);
+        let mut row = vec![format!("  {account}"), commodity.clone()];
+        for (i, amount) in amounts.iter().enumerate() {
+            let amount = amount.checked_mul(sign).ok_or_else(|| {
+                error::out_of_range(format!("the balance of {account} in {commodity}"))
+            })?;
+            total[i] = total[i]
+                .checked_add(amount)
+                .ok_or_else(|| error::out_of_range(format!("the total {name} in {commodity}")))?;
+            row.push(amount.to_string());
+        }
+        table.push(row);
+    }
+
+    for (commodity, amounts) in &totals {
+        let mut row = vec![format!("total {name}"), commodity.clone()];
+        row.extend(amounts.iter().map(|x| x.to_string()));
+        table.push(row);
+    }
+
+    Ok(totals)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// A plain text table, whose columns are as wide as their widest cell.
+#[derive(Debug)]
+pub struct Table {
+    columns: Vec<(String, Align)>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new<S: ToString>(columns: &[(S, Align)]) -> Self {
+        Self {
+            columns: columns
+                .iter()
+                .map(|(name, align)| (name.to_string(), *align))
+                .collect(),
+            rows: vec![],
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<String>) {
+        assert_eq!(
+            row.len(),
+            self.columns.len(),
+            "Row has the wrong number of cells."
+        );
+        self.rows.push(row);
+    }
+
+    fn write_row(
+        f: &mut std::fmt::Formatter<'_>,
+        cells: &[&str],
+        columns: &[(String, Align)],
+        widths: &[usize],
+    ) -> std::fmt::Result {
+        let line = cells
+            .iter()
+            .zip(columns)
+            .zip(widths)
+            .map(|((cell, (_, align)), width)| {
+                let padding = " ".repeat(width - utils::display_width(cell));
+                match align {
+                    Align::Left => format!("{cell}{padding}"),
+                    Align::Right => format!("{padding}{cell}"),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(f, "{}", line.trim_end())
+    }
+}
+
+impl std::fmt::Display for Table {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| {
+                self.rows
+                    .iter()
+                    .map(|row| utils::display_width(&row[i]))
+                    .chain([utils::display_width(name)])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let header: Vec<&str> = self.columns.iter().map(|(name, _)| name.as_str()).collect();
+        Self::write_row(f, &header, &self.columns, &widths)?;
+        for row in &self.rows {
+            let cells: Vec<&str> = row.iter().map(|x| x.as_str()).collect();
+            Self::write_row(f, &cells, &self.columns, &widths)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cli::testing;
+
+    #[rstest::rstest]
+    #[case::default_root("expense/food", Some(output::AccountType::Expense))]
+    #[case::declared("cost/rent", Some(output::AccountType::Expense))]
+    #[case::most_specific_declaration("assets/loan", Some(output::AccountType::Liabilities))]
+    #[case::unknown("misc", None)]
+    fn test_classify(#[case] account: &str, #[case] expected: Option<output::AccountType>) {
+        let mut journal = output::Journal::default();
+        journal
+            .accounts
+            .insert("cost".to_string(), output::AccountType::Expense);
+        journal
+            .accounts
+            .insert("assets/loan".to_string(), output::AccountType::Liabilities);
+
+        assert_eq!(classify(&journal, account), expected);
+    }
+
+    #[rstest::rstest]
+    #[case::monthly(Period::Monthly, "2026-11-15", "2026-11", "2026-12-01")]
+    #[case::quarterly(Period::Quarterly, "2026-11-15", "2026-Q4", "2027-01-01")]
+    #[case::yearly(Period::Yearly, "2026-11-15", "2026", "2027-01-01")]
+    fn test_period(
+        #[case] period: Period,
+        #[case] date: NaiveDate,
+        #[case] name: &str,
+        #[case] next: NaiveDate,
+    ) {
+        let start = period.start(date);
+        assert_eq!(period.name(start), name);
+        assert_eq!(period.next(start), next);
+    }
+
+    fn journal(amounts: &[i64]) -> output::Journal {
+        output::Journal {
+            transactions: amounts
+                .iter()
+                .map(|x| testing::transaction("2026-01-01T00:00:00+09:00", &[("assets/cash", *x)]))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[rstest::rstest]
+    #[case::in_period(false)]
+    #[case::cumulative(true)]
+    fn test_compute_balances_out_of_range(#[case] cumulative: bool) {
+        let mut journal = journal(&[i64::MAX, 1]);
+        if cumulative {
+            journal.transactions[1].header.timestamp =
+                chrono::DateTime::parse_from_rfc3339("2026-02-01T00:00:00+09:00").unwrap();
+        }
+        let starts = periods(&journal, Period::Monthly);
+
+        compute_balances(
+            &journal,
+            &starts,
+            &[output::AccountType::Assets],
+            cumulative,
+        )
+        .expect_err("Should have failed.");
+    }
+
+    #[test]
+    fn test_add_section_out_of_range() {
+        let journal = journal(&[i64::MIN]);
+        let starts = periods(&journal, Period::Monthly);
+        let balances = compute_balances(&journal, &starts, &[output::AccountType::Assets], false)
+            .expect("Failed.");
+
+        add_section(
+            &mut period_table(Period::Monthly, &starts),
+            "assets",
+            &balances,
+            1,
+        )
+        .expect("Failed.");
+        add_section(
+            &mut period_table(Period::Monthly, &starts),
+            "assets",
+            &balances,
+            -1,
+        )
+        .expect_err("Should have failed.");
+    }
+
+    #[test]
+    fn test_table() {
+        let mut table = Table::new(&[("account", Align::Left), ("amount", Align::Right)]);
+        table.push(vec!["expense/food".to_string(), "100".to_string()]);
+        table.push(vec!["income".to_string(), "-20000".to_string()]);
+
+        assert_eq!(
+            table.to_string(),
+            "account       amount\nexpense/food     100\nincome        -20000\n"
+        );
+    }
+}
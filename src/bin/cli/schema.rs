@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+
+use boki::output;
+
+fn render_schema() -> String {
+    let schema = output::json_schema();
+    serde_json::to_string_pretty(&schema).expect("Failed to serialize the JSON schema.") + "\n"
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+pub fn run(args: &Args) {
+    let output_str = render_schema();
+
+    match &args.output {
+        None => print!("{output_str}"),
+        Some(x) => std::fs::write(x, output_str).expect("Failed to write output file."),
+    };
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_schema_matches_golden_file() {
+        let expected = std::fs::read_to_string("docs/schema/journal.schema.json")
+            .expect("Could not read golden file.");
+
+        assert_eq!(
+            super::render_schema(),
+            expected,
+            "The export schema changed. If this is intentional, regenerate the golden file with `boki schema -o docs/schema/journal.schema.json` and bump `output::SCHEMA_VERSION` if the change is not backwards-compatible."
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! Shared helpers for the tests of the commands.
+use boki::output;
+use proptest::prelude::*;
+
+/// Arbitrary text built from pieces of journals, which reaches far more of the lexer and the
+/// parsers than random characters would, e.g. `2026-01-01 ;円( let\n  ---`.
+pub fn journal_fragments() -> impl Strategy<Value = String> {
+    let fragment = prop_oneof![
+        prop::sample::select(vec![
+            "2026-01-01",
+            "12:30",
+            ":45.123",
+            "+09:00",
+            "Z",
+            "*",
+            "!",
+            "\n",
+            "  ",
+            " ",
+            "\t",
+            "---",
+            "a: b",
+            ";",
+            "/",
+            "//",
+            "-",
+            "+",
+            "(",
+            ")",
+            "=",
+            "1,000",
+            "10%",
+            "\"",
+            "\\",
+            "set",
+            "let",
+            "auto",
+            "budget",
+            "account",
+            "round",
+            "with",
+            "end",
+            "monthly",
+            "JPY",
+            "円",
+            "€",
+            "é",
+        ])
+        .prop_map(str::to_string),
+        "[ -~]",
+    ];
+    prop::collection::vec(fragment, 0..40).prop_map(|x| x.concat())
+}
+
+/// A transaction in JPY with the given postings, e.g. `[("expense/food", 1000), ("assets/cash", -1000)]`.
+pub fn transaction(timestamp: &str, postings: &[(&str, i64)]) -> output::Transaction {
+    output::Transaction {
+        header: output::TransactionHeader {
+            timestamp: chrono::DateTime::parse_from_rfc3339(timestamp).unwrap(),
+            status: None,
+            description: None,
+            attributes: Default::default(),
+        },
+        postings: postings
+            .iter()
+            .map(|(account, amount)| output::Posting {
+                account: account.to_string(),
+                commodity: "JPY".to_string(),
+                amount: *amount,
+                ..Default::default()
+            })
+            .collect(),
+        conversion: None,
+    }
+}
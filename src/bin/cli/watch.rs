@@ -0,0 +1,121 @@
+//! Re-runs a command whenever one of its journal files changes.
+//!
+//! The files are polled, since a journal is only a handful of files and a change only has to show up
+//! within a fraction of a second.
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use crate::error::CLIError;
+
+use super::{balance_sheet, budget, check, export, income_statement};
+
+#[derive(clap::Subcommand)]
+pub enum Command {
+    /// Compiles the journal and reports any errors
+    Check(check::Args),
+    Export(export::Args),
+    /// Income statement
+    #[command(name = "is")]
+    IncomeStatement(income_statement::Args),
+    /// Balance sheet
+    #[command(name = "bs")]
+    BalanceSheet(balance_sheet::Args),
+    Budget(budget::Args),
+}
+
+impl Command {
+    fn journal(&self) -> &export::JournalArgs {
+        match self {
+            Command::Check(args) => &args.journal,
+            Command::Export(args) => &args.journal,
+            Command::IncomeStatement(args) => &args.journal,
+            Command::BalanceSheet(args) => &args.journal,
+            Command::Budget(args) => &args.journal,
+        }
+    }
+
+    fn run(&self) -> export::Result<()> {
+        match self {
+            Command::Check(args) => check::run(args),
+            Command::Export(args) => export::run(args),
+            Command::IncomeStatement(args) => income_statement::run(args),
+            Command::BalanceSheet(args) => balance_sheet::run(args),
+            Command::Budget(args) => budget::run(args),
+        }
+    }
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// How often to look for changes, in milliseconds.
+    #[arg(long, default_value_t = 500)]
+    interval: u64,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// What is known about a file in order to tell whether it changed, or `None` if it cannot be read.
+type FileState = Option<(SystemTime, u64)>;
+
+fn file_states(files: &[PathBuf]) -> Vec<FileState> {
+    files
+        .iter()
+        .map(|file| {
+            let metadata = std::fs::metadata(file).ok()?;
+            Some((metadata.modified().ok()?, metadata.len()))
+        })
+        .collect()
+}
+
+/// Runs the command, and keeps running even if it fails, since the journal is probably being edited.
+fn run_once(command: &Command) {
+    // Clears the terminal, so that only the latest output is shown.
+    print!("\x1b[2J\x1b[H");
+    let _ = std::io::stdout().flush();
+    if let Err(e) = command.run() {
+        eprintln!("{}", e.format());
+    }
+    eprintln!(
+        "[{}] Watching {} file(s) for changes.",
+        chrono::Local::now().format("%H:%M:%S"),
+        command.journal().files.len()
+    );
+}
+
+pub fn run(args: &Args) -> ! {
+    let files = &args.command.journal().files;
+    let interval = Duration::from_millis(args.interval);
+
+    let mut last_states = None;
+    loop {
+        // The states are taken before running, so that changes made while running are not missed.
+        let states = file_states(files);
+        if last_states.as_ref() != Some(&states) {
+            run_once(&args.command);
+            last_states = Some(states);
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_file_states_change_with_content() {
+        let file = std::env::temp_dir().join("boki-test-watch.boki");
+        std::fs::write(&file, "set default_commodity JPY\n").expect("Failed.");
+        let files = [file.clone(), file.with_extension("missing")];
+
+        let before = file_states(&files);
+        assert!(before[0].is_some());
+        assert!(before[1].is_none());
+        assert_eq!(file_states(&files), before);
+
+        std::fs::write(&file, "set default_commodity USD\n\n").expect("Failed.");
+        assert_ne!(file_states(&files), before);
+    }
+}
@@ -7,8 +7,21 @@ use error::CLIErrorResult;
 
 #[derive(Subcommand)]
 enum Commands {
+    Budget(cli::budget::Args),
+    /// Compiles the journal and reports any errors
+    Check(cli::check::Args),
+    Close(cli::close::Args),
+    /// Income statement
+    #[command(name = "is")]
+    IncomeStatement(cli::income_statement::Args),
+    /// Balance sheet
+    #[command(name = "bs")]
+    BalanceSheet(cli::balance_sheet::Args),
     Export(cli::export::Args),
     Format(cli::format::Args),
+    Schema(cli::schema::Args),
+    /// Re-runs a command whenever the journal changes
+    Watch(cli::watch::Args),
 }
 
 #[derive(Parser)]
@@ -22,7 +35,14 @@ fn main() {
     let cli = Cli::parse();
 
     match &cli.command {
+        Commands::Budget(args) => cli::budget::run(args).or_quit(),
+        Commands::Check(args) => cli::check::run(args).or_quit(),
+        Commands::Close(args) => cli::close::run(args).or_quit(),
+        Commands::IncomeStatement(args) => cli::income_statement::run(args).or_quit(),
+        Commands::BalanceSheet(args) => cli::balance_sheet::run(args).or_quit(),
         Commands::Export(args) => cli::export::run(args).or_quit(),
         Commands::Format(args) => cli::format::run(args).or_quit(),
+        Commands::Schema(args) => cli::schema::run(args),
+        Commands::Watch(args) => cli::watch::run(args),
     };
 }
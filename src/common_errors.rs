@@ -1,19 +1,19 @@
 //! A module to house error types commonly required in binaries, as well as their various trait implementations.
-use crate::{lex, parsing, utils};
+use crate::{compile, lex, parsing, utils};
 use std::path::PathBuf;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct FileLexError {
-    pub filename: Rc<PathBuf>,
-    pub content: Rc<str>,
+    pub filename: Arc<PathBuf>,
+    pub content: Arc<str>,
     pub error: lex::LexerError,
 }
 
 impl FileLexError {
     pub fn map_from_lexer_error(
-        filename: Rc<PathBuf>,
-        content: Rc<str>,
+        filename: Arc<PathBuf>,
+        content: Arc<str>,
     ) -> impl FnOnce(lex::LexerError) -> Self {
         move |error| Self {
             filename,
@@ -48,17 +48,17 @@ impl std::fmt::Display for FileLexError {
 
 #[derive(Debug)]
 pub struct FileParseError {
-    pub filename: Rc<PathBuf>,
-    pub content: Rc<str>,
-    pub decorated_tokens: Rc<[lex::DecoratedToken]>,
+    pub filename: Arc<PathBuf>,
+    pub content: Arc<str>,
+    pub decorated_tokens: Arc<[lex::DecoratedToken]>,
     pub error: parsing::ParserError,
 }
 
 impl FileParseError {
     pub fn map_from_parser_error(
-        filename: Rc<PathBuf>,
-        content: Rc<str>,
-        decorated_tokens: Rc<[lex::DecoratedToken]>,
+        filename: Arc<PathBuf>,
+        content: Arc<str>,
+        decorated_tokens: Arc<[lex::DecoratedToken]>,
     ) -> impl FnOnce(parsing::ParserError) -> Self {
         move |error| Self {
             filename,
@@ -97,3 +97,50 @@ impl std::fmt::Display for FileParseError {
         Ok(())
     }
 }
+
+#[derive(Debug)]
+pub struct FileCompileError {
+    pub filename: Arc<PathBuf>,
+    pub content: Arc<str>,
+    /// The location in `content` of the node which failed to compile.
+    pub location: usize,
+    pub error: compile::CompilationError,
+}
+
+impl FileCompileError {
+    pub fn map_from_compilation_error(
+        filename: Arc<PathBuf>,
+        content: Arc<str>,
+        location: usize,
+    ) -> impl FnOnce(compile::CompilationError) -> Self {
+        move |error| Self {
+            filename,
+            content,
+            location,
+            error,
+        }
+    }
+}
+
+impl std::fmt::Display for FileCompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (line, character) =
+            utils::get_position_in_content(self.content.as_ref(), self.location);
+
+        writeln!(
+            f,
+            "while compiling file: {}:{}:{}:",
+            self.filename.as_os_str().to_str().unwrap(),
+            line + 1,
+            character + 1
+        )?;
+        writeln!(
+            f,
+            "{}",
+            utils::pretty_print_location(self.content.as_ref(), self.location)
+        )?;
+        writeln!(f, "{}", self.error)?;
+
+        Ok(())
+    }
+}
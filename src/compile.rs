@@ -11,12 +11,36 @@
 use crate::ast;
 use crate::output;
 
+mod account;
+mod auto_rule;
+mod budget;
+mod scope;
 mod set_attribute;
+mod symbols;
+mod timezone;
 mod transaction;
 
 #[derive(Debug)]
 pub enum CompilationError {
     GeneralError(String),
+    /// An amount, or a sum of amounts, which does not fit in a 64-bit integer.
+    AmountOutOfRange(String),
+    /// A balance of the given commodity which cannot be negated, i.e. `i64::MIN`.
+    UnnegatableBalance(String),
+}
+
+impl std::fmt::Display for CompilationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompilationError::GeneralError(x) => write!(f, "{x}"),
+            CompilationError::AmountOutOfRange(x) => write!(f, "Amount is out of range: {x}"),
+            CompilationError::UnnegatableBalance(x) => write!(
+                f,
+                "The balance of {x} is {}, which cannot be negated.",
+                i64::MIN
+            ),
+        }
+    }
 }
 
 impl CompilationError {
@@ -27,12 +51,74 @@ impl CompilationError {
 
 pub type CompilationResult<T> = Result<T, CompilationError>;
 
-pub fn compile_node(node: &ast::ASTNode, journal: &mut output::Journal) -> CompilationResult<()> {
+/// The state which is carried over between the nodes of a journal.
+#[derive(Debug, Default)]
+pub struct CompilationContext {
+    pub journal: output::Journal,
+    /// The auto rules seen so far. A rule only applies to the transactions which come after it.
+    rules: Vec<ast::AutoRule>,
+    /// The attributes of the currently open `with` scopes, from the outermost to the innermost one.
+    scopes: Vec<serde_yaml::Mapping>,
+    /// The constants defined with `let` so far.
+    symbols: symbols::SymbolTable,
+    /// The parsed `default_timezone`, applied to timestamps without an offset.
+    timezone: timezone::Timezone,
+}
+
+impl CompilationContext {
+    /// Ends a file, after checking that all of its scopes have been closed. The constants and scopes
+    /// of a file do not carry over to the next one, while everything else (e.g. settings) does.
+    pub fn finish_file(&mut self) -> CompilationResult<()> {
+        if !self.scopes.is_empty() {
+            return Err(CompilationError::from_string(
+                "Found `with` without a matching `end`.",
+            ));
+        }
+
+        self.symbols = Default::default();
+        Ok(())
+    }
+
+    /// Returns the compiled journal of a single file, after checking that all scopes have been closed.
+    pub fn finish(mut self) -> CompilationResult<output::Journal> {
+        self.finish_file()?;
+        Ok(self.journal)
+    }
+}
+
+pub fn compile_node(node: &ast::ASTNode, ctx: &mut CompilationContext) -> CompilationResult<()> {
     match node {
-        ast::ASTNode::Transaction(t) => transaction::TransactionCompiler::compile(t, journal),
-        ast::ASTNode::SetAttribute(name, value) => {
-            set_attribute::SetAttributeCompiler::compile(name, value, journal)
+        ast::ASTNode::Transaction(t) => {
+            let t = scope::ScopeCompiler::apply(&ctx.scopes, t);
+            transaction::TransactionCompiler::compile(
+                &t,
+                &ctx.rules,
+                &ctx.symbols,
+                &ctx.timezone,
+                &mut ctx.journal,
+            )
         }
+        ast::ASTNode::SetAttribute(name, value) => set_attribute::SetAttributeCompiler::compile(
+            name,
+            value,
+            &ctx.symbols,
+            &mut ctx.journal,
+            &mut ctx.timezone,
+        ),
+        ast::ASTNode::AutoRule(rule) => {
+            auto_rule::AutoRuleCompiler::compile(rule, &ctx.symbols, &mut ctx.rules)
+        }
+        ast::ASTNode::Budget(b) => {
+            budget::BudgetCompiler::compile(b, &ctx.symbols, &mut ctx.journal)
+        }
+        ast::ASTNode::AccountDeclaration(decl) => {
+            account::AccountCompiler::compile(decl, &mut ctx.journal)
+        }
+        ast::ASTNode::With(attributes) => {
+            scope::ScopeCompiler::compile_with(attributes, &mut ctx.scopes)
+        }
+        ast::ASTNode::End => scope::ScopeCompiler::compile_end(&mut ctx.scopes),
+        ast::ASTNode::Let(name, value) => ctx.symbols.define(name, value),
     }
 }
 
@@ -42,21 +128,23 @@ mod test {
 
     fn sample_transaction() -> ast::Transaction {
         ast::Transaction {
-            header: ast::TransactionHeader {
-                timestamp: chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05.000+09:00")
-                    .unwrap(),
-                attributes: serde_yaml::Mapping::default(),
-            },
+            header: ast::TransactionHeader::new(
+                chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05.000+09:00")
+                    .unwrap()
+                    .into(),
+            ),
             postings: vec![
                 ast::Posting {
                     account: "foo".to_string(),
                     commodity: Some("JPY".to_string()),
-                    amount: Some(1000),
+                    amount: Some(1000.into()),
+                    ..Default::default()
                 },
                 ast::Posting {
                     account: "bar".to_string(),
                     commodity: Some("JPY".to_string()),
-                    amount: Some(-1000),
+                    amount: Some((-1000).into()),
+                    ..Default::default()
                 },
             ],
         }
@@ -65,16 +153,153 @@ mod test {
     #[test]
     fn test_compile_simple() {
         let node = ast::ASTNode::Transaction(sample_transaction());
-        let mut journal = output::Journal::default();
-        compile_node(&node, &mut journal).expect("Compilation failed.");
+        let mut ctx = CompilationContext::default();
+        compile_node(&node, &mut ctx).expect("Compilation failed.");
     }
 
     #[test]
     fn test_compile_node_simple_transaction() {
         let node = ast::ASTNode::Transaction(sample_transaction());
-        let mut journal = output::Journal::default();
-        compile_node(&node, &mut journal).expect("Compilation failed.");
+        let mut ctx = CompilationContext::default();
+        compile_node(&node, &mut ctx).expect("Compilation failed.");
+
+        assert_eq!(ctx.journal.transactions.len(), 1);
+    }
+
+    #[test]
+    fn test_default_timezone_applies_to_following_transactions() {
+        let mut t = sample_transaction();
+        t.header.timestamp.offset = None;
+
+        let mut ctx = CompilationContext::default();
+        for node in [
+            ast::ASTNode::Transaction(t.clone()),
+            ast::ASTNode::SetAttribute(
+                "default_timezone".to_string(),
+                ast::Value::Identifier("Asia/Tokyo".to_string()),
+            ),
+            ast::ASTNode::Transaction(t),
+        ] {
+            compile_node(&node, &mut ctx).expect("Compilation failed.");
+        }
+
+        let timestamps: Vec<_> = ctx
+            .journal
+            .transactions
+            .iter()
+            .map(|t| t.header.timestamp.to_rfc3339())
+            .collect();
+        assert_eq!(
+            timestamps,
+            vec!["2026-01-02T03:04:05+00:00", "2026-01-02T03:04:05+09:00"]
+        );
+    }
+
+    fn mapping(yaml: &str) -> serde_yaml::Mapping {
+        serde_yaml::from_str(yaml).expect("Invalid test case.")
+    }
+
+    #[test]
+    fn test_scopes_merge_attributes() {
+        let mut t = sample_transaction();
+        t.header.attributes = mapping("category: food");
+
+        let mut ctx = CompilationContext::default();
+        for node in [
+            ast::ASTNode::With(mapping("trip: honeymoon\ncategory: travel")),
+            ast::ASTNode::With(mapping("city: Honolulu")),
+            ast::ASTNode::Transaction(t.clone()),
+            ast::ASTNode::End,
+            ast::ASTNode::Transaction(t),
+            ast::ASTNode::End,
+            ast::ASTNode::Transaction(sample_transaction()),
+        ] {
+            compile_node(&node, &mut ctx).expect("Compilation failed.");
+        }
+        let journal = ctx.finish().expect("Compilation failed.");
+
+        let attributes: Vec<_> = journal
+            .transactions
+            .iter()
+            .map(|t| t.header.attributes.clone())
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![
+                mapping("category: food\ncity: Honolulu\ntrip: honeymoon"),
+                mapping("category: food\ntrip: honeymoon"),
+                mapping("{}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unmatched_end_fails() {
+        let mut ctx = CompilationContext::default();
+        compile_node(&ast::ASTNode::End, &mut ctx).expect_err("Should have failed.");
+    }
+
+    #[test]
+    fn test_unclosed_scope_fails() {
+        let mut ctx = CompilationContext::default();
+        compile_node(&ast::ASTNode::With(mapping("trip: honeymoon")), &mut ctx)
+            .expect("Compilation failed.");
+        ctx.finish().expect_err("Should have failed.");
+    }
+
+    #[test]
+    fn test_variables() {
+        let rent = || ast::Expression::Variable("rent".to_string());
+        let mut t = sample_transaction();
+        t.postings[0].amount = Some(rent());
+        t.postings[1].amount = Some(ast::Expression::Negation(Box::new(rent())));
+
+        let mut ctx = CompilationContext::default();
+        for node in [
+            ast::ASTNode::Let("rent".to_string(), 85000.into()),
+            ast::ASTNode::Let("start".to_string(), 4.into()),
+            ast::ASTNode::SetAttribute(
+                "fiscal_year_start".to_string(),
+                ast::Value::Identifier("start".to_string()),
+            ),
+            ast::ASTNode::Transaction(t),
+        ] {
+            compile_node(&node, &mut ctx).expect("Compilation failed.");
+        }
+
+        assert_eq!(ctx.journal.header.fiscal_year_start, Some(4));
+        assert_eq!(ctx.journal.transactions[0].postings[0].amount, 85000);
+        assert_eq!(ctx.journal.transactions[0].postings[1].amount, -85000);
+    }
+
+    #[test]
+    fn test_constants_only_apply_to_integer_settings() {
+        let mut ctx = CompilationContext::default();
+        for node in [
+            ast::ASTNode::Let("JPY".to_string(), 1.into()),
+            ast::ASTNode::SetAttribute(
+                "default_commodity".to_string(),
+                ast::Value::Identifier("JPY".to_string()),
+            ),
+            ast::ASTNode::SetAttribute(
+                "title".to_string(),
+                ast::Value::Identifier("JPY".to_string()),
+            ),
+        ] {
+            compile_node(&node, &mut ctx).expect("Compilation failed.");
+        }
+
+        assert_eq!(ctx.journal.header.default_commodity, "JPY");
+        assert_eq!(ctx.journal.header.title.as_deref(), Some("JPY"));
+    }
+
+    #[test]
+    fn test_variable_must_be_defined_first() {
+        let mut t = sample_transaction();
+        t.postings[0].amount = Some(ast::Expression::Variable("rent".to_string()));
+        t.postings[1].amount = None;
 
-        assert_eq!(journal.transactions.len(), 1);
+        let mut ctx = CompilationContext::default();
+        compile_node(&ast::ASTNode::Transaction(t), &mut ctx).expect_err("Should have failed.");
     }
 }
@@ -0,0 +1,73 @@
+use super::{CompilationError, CompilationResult};
+use crate::{ast, output};
+
+pub struct AccountCompiler;
+
+impl AccountCompiler {
+    fn parse_account_type(s: &str) -> CompilationResult<output::AccountType> {
+        match s {
+            "assets" => Ok(output::AccountType::Assets),
+            "liabilities" => Ok(output::AccountType::Liabilities),
+            "equity" => Ok(output::AccountType::Equity),
+            "income" => Ok(output::AccountType::Income),
+            "expense" => Ok(output::AccountType::Expense),
+            _ => Err(CompilationError::GeneralError(format!(
+                "Invalid account type: {s}. Must be one of assets, liabilities, equity, income or expense."
+            ))),
+        }
+    }
+
+    pub fn compile(
+        decl: &ast::AccountDeclaration,
+        journal: &mut output::Journal,
+    ) -> CompilationResult<()> {
+        let account_type = Self::parse_account_type(&decl.account_type)?;
+
+        if journal.accounts.contains_key(&decl.account) {
+            return Err(CompilationError::GeneralError(format!(
+                "Account already declared: {}",
+                decl.account
+            )));
+        }
+
+        journal.accounts.insert(decl.account.clone(), account_type);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn declaration(account_type: &str) -> ast::AccountDeclaration {
+        ast::AccountDeclaration {
+            account: "cost".to_string(),
+            account_type: account_type.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_simple() {
+        let mut journal = output::Journal::default();
+        AccountCompiler::compile(&declaration("expense"), &mut journal).expect("Failed.");
+        assert_eq!(
+            journal.accounts.get("cost"),
+            Some(&output::AccountType::Expense)
+        );
+    }
+
+    #[test]
+    fn test_invalid_type_fails() {
+        let mut journal = output::Journal::default();
+        AccountCompiler::compile(&declaration("costs"), &mut journal)
+            .expect_err("Should have failed.");
+    }
+
+    #[test]
+    fn test_redeclaration_fails() {
+        let mut journal = output::Journal::default();
+        AccountCompiler::compile(&declaration("expense"), &mut journal).expect("Failed.");
+        AccountCompiler::compile(&declaration("income"), &mut journal)
+            .expect_err("Should have failed.");
+    }
+}
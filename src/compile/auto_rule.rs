@@ -0,0 +1,184 @@
+//! Automated posting rules, which add postings to the transactions they match.
+use super::symbols::SymbolTable;
+use super::{CompilationError, CompilationResult};
+use crate::{ast, output, utils};
+
+pub struct AutoRuleCompiler;
+
+impl AutoRuleCompiler {
+    pub fn compile(
+        rule: &ast::AutoRule,
+        symbols: &SymbolTable,
+        rules: &mut Vec<ast::AutoRule>,
+    ) -> CompilationResult<()> {
+        if rule.postings.is_empty() {
+            return Err(CompilationError::from_string(
+                "An auto rule must have at least one posting.",
+            ));
+        }
+        if rule.account.is_none() && rule.attributes.is_empty() {
+            return Err(CompilationError::from_string(
+                "An auto rule must have an account or attributes to match.",
+            ));
+        }
+        for p in &rule.postings {
+            match &p.amount {
+                // Fixed amounts only depend on constants, which cannot change, so we can check them early.
+                ast::AutoAmount::Fixed(x) => {
+                    symbols.evaluate(x)?;
+                }
+                ast::AutoAmount::Percentage(_) if rule.account.is_none() => {
+                    return Err(CompilationError::GeneralError(format!(
+                        "Auto rule posting to {} has a percentage, but the rule has no account to take it of.",
+                        p.account
+                    )));
+                }
+                ast::AutoAmount::Percentage(_) => {}
+            }
+        }
+
+        rules.push(rule.clone());
+        Ok(())
+    }
+
+    fn matches_attributes(rule: &ast::AutoRule, t: &ast::Transaction) -> bool {
+        rule.attributes
+            .iter()
+            .all(|(k, v)| t.header.attributes.get(k) == Some(v))
+    }
+
+    /// Returns the postings of `t` matched by `rule`. A rule without an account matches the transaction
+    /// once, without a posting.
+    fn matched_postings<'a>(
+        rule: &ast::AutoRule,
+        t: &'a ast::Transaction,
+    ) -> Vec<Option<&'a ast::Posting>> {
+        if !Self::matches_attributes(rule, t) {
+            return vec![];
+        }
+        match &rule.account {
+            Some(account) => t
+                .postings
+                .iter()
+                .filter(|p| utils::is_same_or_subaccount(account, &p.account))
+                .map(Some)
+                .collect(),
+            None => vec![None],
+        }
+    }
+
+    /// Computes the share of `amount`, rounding half away from zero.
+    fn share(amount: i64, basis_points: i64) -> CompilationResult<i64> {
+        let result = utils::divide_rounded(amount as i128 * basis_points as i128, 10000);
+
+        i64::try_from(result).map_err(|_| {
+            CompilationError::AmountOutOfRange(format!("{basis_points} basis points of {amount}"))
+        })
+    }
+
+    /// Generates the postings of all rules which match any of the hand-written postings of `t`.
+    pub fn apply(
+        rules: &[ast::AutoRule],
+        t: &ast::Transaction,
+        symbols: &SymbolTable,
+        journal: &output::Journal,
+    ) -> CompilationResult<Vec<output::Posting>> {
+        let mut postings = vec![];
+
+        for rule in rules {
+            for p in Self::matched_postings(rule, t) {
+                let commodity = p
+                    .and_then(|p| p.commodity.clone())
+                    .unwrap_or(journal.header.default_commodity.clone());
+
+                for generated in &rule.postings {
+                    let amount = match &generated.amount {
+                        ast::AutoAmount::Fixed(x) => symbols.evaluate(x)?,
+                        ast::AutoAmount::Percentage(x) => {
+                            // Rules without an account cannot have percentages, so there is always a matched posting.
+                            let Some(amount) = p.and_then(|p| p.amount.as_ref()) else {
+                                return Err(CompilationError::GeneralError(format!(
+                                    "Auto rule posting to {} matched a posting without an amount.",
+                                    generated.account
+                                )));
+                            };
+                            Self::share(symbols.evaluate(amount)?, *x)?
+                        }
+                    };
+
+                    postings.push(output::Posting {
+                        account: generated.account.clone(),
+                        commodity: generated.commodity.clone().unwrap_or(commodity.clone()),
+                        amount,
+                        generated: true,
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+
+        Ok(postings)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[rstest::rstest]
+    #[case::exact(1000, 1000, 100)]
+    #[case::round_half_up(5, 1000, 1)]
+    #[case::round_down(4, 1000, 0)]
+    #[case::round_half_away_from_zero(-5, 1000, -1)]
+    #[case::negative_rate(1000, -1000, -100)]
+    fn test_share(#[case] amount: i64, #[case] basis_points: i64, #[case] expected: i64) {
+        assert_eq!(
+            AutoRuleCompiler::share(amount, basis_points).expect("Failed."),
+            expected
+        );
+    }
+
+    fn fee_rule(account: Option<&str>, amount: ast::AutoAmount) -> ast::AutoRule {
+        let mut attributes = serde_yaml::Mapping::default();
+        attributes.insert("shop".into(), "online".into());
+        ast::AutoRule {
+            account: account.map(str::to_string),
+            attributes,
+            postings: vec![ast::AutoPosting {
+                account: "expense/fee".to_string(),
+                commodity: None,
+                amount,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_compile_without_account() {
+        let mut rules = vec![];
+        let rule = fee_rule(None, ast::AutoAmount::Fixed(ast::Expression::Amount(100)));
+        AutoRuleCompiler::compile(&rule, &Default::default(), &mut rules).expect("Failed.");
+        assert_eq!(rules.len(), 1);
+    }
+
+    #[test]
+    fn test_compile_without_account_or_attributes_fails() {
+        let mut rule = fee_rule(None, ast::AutoAmount::Fixed(ast::Expression::Amount(100)));
+        rule.attributes = Default::default();
+        AutoRuleCompiler::compile(&rule, &Default::default(), &mut vec![])
+            .expect_err("Should have failed.");
+    }
+
+    #[test]
+    fn test_compile_percentage_without_account_fails() {
+        let rule = fee_rule(None, ast::AutoAmount::Percentage(1000));
+        AutoRuleCompiler::compile(&rule, &Default::default(), &mut vec![])
+            .expect_err("Should have failed.");
+        let rule = fee_rule(Some("expense"), ast::AutoAmount::Percentage(1000));
+        AutoRuleCompiler::compile(&rule, &Default::default(), &mut vec![]).expect("Failed.");
+    }
+
+    #[test]
+    fn test_share_out_of_range() {
+        AutoRuleCompiler::share(i64::MAX, 20000).expect_err("Should have failed.");
+    }
+}
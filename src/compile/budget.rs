@@ -0,0 +1,107 @@
+use super::symbols::SymbolTable;
+use super::{CompilationError, CompilationResult};
+use crate::{ast, output};
+
+pub struct BudgetCompiler;
+
+impl BudgetCompiler {
+    fn parse_period(s: &str) -> CompilationResult<output::BudgetPeriod> {
+        match s {
+            "monthly" => Ok(output::BudgetPeriod::Monthly),
+            "quarterly" => Ok(output::BudgetPeriod::Quarterly),
+            "yearly" => Ok(output::BudgetPeriod::Yearly),
+            _ => Err(CompilationError::GeneralError(format!(
+                "Invalid budget period: {s}. Must be one of monthly, quarterly or yearly."
+            ))),
+        }
+    }
+
+    pub fn compile(
+        b: &ast::Budget,
+        symbols: &SymbolTable,
+        journal: &mut output::Journal,
+    ) -> CompilationResult<()> {
+        let budget = output::Budget {
+            period: Self::parse_period(&b.period)?,
+            account: b.account.clone(),
+            commodity: b
+                .commodity
+                .clone()
+                .unwrap_or(journal.header.default_commodity.clone()),
+            amount: symbols.evaluate(&b.amount)?,
+        };
+
+        if journal.budgets.iter().any(|x| {
+            x.period == budget.period
+                && x.account == budget.account
+                && x.commodity == budget.commodity
+        }) {
+            return Err(CompilationError::GeneralError(format!(
+                "Duplicate budget for {} in {}.",
+                budget.account, budget.commodity
+            )));
+        }
+
+        journal.budgets.push(budget);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn budget(period: &str, commodity: Option<&str>) -> ast::Budget {
+        ast::Budget {
+            period: period.to_string(),
+            account: "expense/food".to_string(),
+            commodity: commodity.map(|x| x.to_string()),
+            amount: 60000.into(),
+        }
+    }
+
+    #[test]
+    fn test_uses_default_commodity() {
+        let mut journal = output::Journal::default();
+        journal.header.default_commodity = "JPY".to_string();
+        BudgetCompiler::compile(&budget("monthly", None), &Default::default(), &mut journal)
+            .expect("Failed.");
+
+        assert_eq!(journal.budgets[0].commodity, "JPY");
+        assert_eq!(journal.budgets[0].period, output::BudgetPeriod::Monthly);
+    }
+
+    #[test]
+    fn test_invalid_period_fails() {
+        let mut journal = output::Journal::default();
+        BudgetCompiler::compile(
+            &budget("daily", Some("JPY")),
+            &Default::default(),
+            &mut journal,
+        )
+        .expect_err("Should have failed.");
+    }
+
+    #[test]
+    fn test_duplicate_budget_fails() {
+        let mut journal = output::Journal::default();
+        BudgetCompiler::compile(
+            &budget("monthly", Some("JPY")),
+            &Default::default(),
+            &mut journal,
+        )
+        .expect("Failed.");
+        BudgetCompiler::compile(
+            &budget("yearly", Some("JPY")),
+            &Default::default(),
+            &mut journal,
+        )
+        .expect("Failed.");
+        BudgetCompiler::compile(
+            &budget("monthly", Some("JPY")),
+            &Default::default(),
+            &mut journal,
+        )
+        .expect_err("Should have failed.");
+    }
+}
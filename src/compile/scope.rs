@@ -0,0 +1,44 @@
+//! Scopes, whose attributes are inherited by all the transactions inside of them.
+use super::{CompilationError, CompilationResult};
+use crate::ast;
+
+pub struct ScopeCompiler;
+
+impl ScopeCompiler {
+    pub fn compile_with(
+        attributes: &serde_yaml::Mapping,
+        scopes: &mut Vec<serde_yaml::Mapping>,
+    ) -> CompilationResult<()> {
+        if attributes.is_empty() {
+            return Err(CompilationError::from_string(
+                "A scope must have at least one attribute.",
+            ));
+        }
+
+        scopes.push(attributes.clone());
+        Ok(())
+    }
+
+    pub fn compile_end(scopes: &mut Vec<serde_yaml::Mapping>) -> CompilationResult<()> {
+        scopes
+            .pop()
+            .map(|_| ())
+            .ok_or_else(|| CompilationError::from_string("Found `end` without a matching `with`."))
+    }
+
+    /// Returns `t` with the attributes of all enclosing scopes merged into it.
+    ///
+    /// The transaction's own attributes take precedence, followed by the innermost scope.
+    pub fn apply(scopes: &[serde_yaml::Mapping], t: &ast::Transaction) -> ast::Transaction {
+        let mut t = t.clone();
+        for scope in scopes.iter().rev() {
+            for (k, v) in scope {
+                if !t.header.attributes.contains_key(k) {
+                    t.header.attributes.insert(k.clone(), v.clone());
+                }
+            }
+        }
+
+        t
+    }
+}
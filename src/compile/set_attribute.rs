@@ -1,16 +1,114 @@
-use super::CompilationResult;
-use crate::output;
+use super::symbols::SymbolTable;
+use super::timezone::Timezone;
+use super::{CompilationError, CompilationResult};
+use crate::{ast, output};
+
+/// Every setting which can be changed with `set`. Each of them is exported in [output::JournalHeader].
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Setting {
+    Title,
+    DefaultCommodity,
+    DefaultTimezone,
+    ConversionPostings,
+    FiscalYearStart,
+}
+
+const SETTINGS: &[(&str, Setting)] = &[
+    ("title", Setting::Title),
+    ("default_commodity", Setting::DefaultCommodity),
+    ("default_timezone", Setting::DefaultTimezone),
+    ("conversion_postings", Setting::ConversionPostings),
+    ("fiscal_year_start", Setting::FiscalYearStart),
+];
 
 pub struct SetAttributeCompiler;
 
 impl SetAttributeCompiler {
+    fn ill_typed(name: &str, expected: &str, value: &ast::Value) -> CompilationError {
+        CompilationError::GeneralError(format!("{name} must be {expected}, got: {value}"))
+    }
+
+    fn string(name: &str, value: &ast::Value) -> CompilationResult<String> {
+        match value {
+            ast::Value::Identifier(x) | ast::Value::String(x) => Ok(x.clone()),
+            _ => Err(Self::ill_typed(name, "a string", value)),
+        }
+    }
+
+    /// Only integer settings can be given a constant, so other settings can have values which are also
+    /// the names of constants, e.g. `default_commodity JPY` after `let JPY = 1`.
+    fn integer(name: &str, value: &ast::Value, symbols: &SymbolTable) -> CompilationResult<i64> {
+        symbols
+            .integer(value)
+            .ok_or_else(|| Self::ill_typed(name, "an integer", value))
+    }
+
+    fn month(name: &str, value: &ast::Value, symbols: &SymbolTable) -> CompilationResult<u32> {
+        match Self::integer(name, value, symbols)? {
+            x @ 1..=12 => Ok(x as u32),
+            x => Err(CompilationError::GeneralError(format!(
+                "{name} must be a month between 1 and 12, got: {x}"
+            ))),
+        }
+    }
+
+    fn bool(name: &str, value: &ast::Value) -> CompilationResult<bool> {
+        match value {
+            ast::Value::Identifier(x) if x == "true" => Ok(true),
+            ast::Value::Identifier(x) if x == "false" => Ok(false),
+            _ => Err(Self::ill_typed(name, "true or false", value)),
+        }
+    }
+
+    fn commodity(name: &str, value: &ast::Value) -> CompilationResult<String> {
+        match value {
+            ast::Value::Identifier(x) if !x.contains('/') => Ok(x.clone()),
+            _ => Err(Self::ill_typed(name, "a commodity", value)),
+        }
+    }
+
+    fn timezone(name: &str, value: &ast::Value) -> CompilationResult<(String, Timezone)> {
+        match value {
+            ast::Value::Identifier(x) | ast::Value::String(x) => {
+                Ok((x.clone(), Timezone::parse(x)?))
+            }
+            _ => Err(Self::ill_typed(name, "a timezone", value)),
+        }
+    }
+
+    /// Changes the setting `name`. Since `default_timezone` is applied to every following transaction,
+    /// it is parsed only once, into `timezone`.
     pub fn compile(
         name: &str,
-        value: &str,
+        value: &ast::Value,
+        symbols: &SymbolTable,
         journal: &mut output::Journal,
+        timezone: &mut Timezone,
     ) -> CompilationResult<()> {
-        if name == "default_commodity" {
-            journal.header.default_commodity = value.to_string();
+        let Some((_, setting)) = SETTINGS.iter().find(|(x, _)| *x == name) else {
+            return Err(CompilationError::GeneralError(format!(
+                "Unknown setting: {name}. Must be one of: {}",
+                SETTINGS
+                    .iter()
+                    .map(|(x, _)| *x)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        };
+
+        let header = &mut journal.header;
+        match setting {
+            Setting::Title => header.title = Some(Self::string(name, value)?),
+            Setting::DefaultCommodity => header.default_commodity = Self::commodity(name, value)?,
+            Setting::DefaultTimezone => {
+                let (x, parsed) = Self::timezone(name, value)?;
+                header.default_timezone = Some(x);
+                *timezone = parsed;
+            }
+            Setting::ConversionPostings => header.conversion_postings = Self::bool(name, value)?,
+            Setting::FiscalYearStart => {
+                header.fiscal_year_start = Some(Self::month(name, value, symbols)?)
+            }
         }
 
         Ok(())
@@ -19,14 +117,117 @@ impl SetAttributeCompiler {
 
 #[cfg(test)]
 mod test {
-    use super::SetAttributeCompiler;
-    use crate::output;
+    use super::{SetAttributeCompiler, Timezone};
+    use crate::compile::CompilationResult;
+    use crate::{ast, output};
+
+    fn identifier(x: &str) -> ast::Value {
+        ast::Value::Identifier(x.to_string())
+    }
+
+    fn compile(
+        name: &str,
+        value: &ast::Value,
+        journal: &mut output::Journal,
+    ) -> CompilationResult<()> {
+        SetAttributeCompiler::compile(
+            name,
+            value,
+            &Default::default(),
+            journal,
+            &mut Timezone::default(),
+        )
+    }
 
     #[test]
     fn test_set_default_commodity() {
         let mut journal = output::Journal::default();
-        SetAttributeCompiler::compile("default_commodity", "JPY", &mut journal).expect("Failed.");
+        compile("default_commodity", &identifier("JPY"), &mut journal).expect("Failed.");
 
         assert_eq!(journal.header.default_commodity, "JPY");
     }
+
+    #[rstest::rstest]
+    #[case::named(identifier("Asia/Tokyo"), "Asia/Tokyo")]
+    #[case::fixed(ast::Value::String("+09:00".to_string()), "+09:00")]
+    fn test_set_default_timezone(#[case] value: ast::Value, #[case] expected: &str) {
+        let mut journal = output::Journal::default();
+        let mut timezone = Timezone::default();
+        SetAttributeCompiler::compile(
+            "default_timezone",
+            &value,
+            &Default::default(),
+            &mut journal,
+            &mut timezone,
+        )
+        .expect("Failed.");
+
+        assert_eq!(journal.header.default_timezone.as_deref(), Some(expected));
+        assert_eq!(
+            timezone,
+            Timezone::parse(expected).expect("Invalid test case.")
+        );
+    }
+
+    #[test]
+    fn test_set_invalid_default_timezone() {
+        let mut journal = output::Journal::default();
+        compile(
+            "default_timezone",
+            &identifier("Mars/Olympus"),
+            &mut journal,
+        )
+        .expect_err("Should have failed.");
+    }
+
+    #[test]
+    fn test_set_unknown_attribute() {
+        let mut journal = output::Journal::default();
+        compile("default_comodity", &identifier("JPY"), &mut journal)
+            .expect_err("Should have failed.");
+    }
+
+    #[test]
+    fn test_set_conversion_postings() {
+        let mut journal = output::Journal::default();
+        compile("conversion_postings", &identifier("true"), &mut journal).expect("Failed.");
+        assert!(journal.header.conversion_postings);
+
+        compile("conversion_postings", &identifier("yes"), &mut journal)
+            .expect_err("Should have failed.");
+    }
+
+    #[test]
+    fn test_set_title() {
+        let mut journal = output::Journal::default();
+        let value = ast::Value::String("Household 2026".to_string());
+        compile("title", &value, &mut journal).expect("Failed.");
+
+        assert_eq!(journal.header.title.as_deref(), Some("Household 2026"));
+    }
+
+    #[rstest::rstest]
+    #[case::valid(ast::Value::Integer(4), Some(4))]
+    #[case::out_of_range(ast::Value::Integer(13), None)]
+    #[case::zero(ast::Value::Integer(0), None)]
+    #[case::wrong_type(identifier("april"), None)]
+    fn test_set_fiscal_year_start(#[case] value: ast::Value, #[case] expected: Option<u32>) {
+        let mut journal = output::Journal::default();
+        let result = compile("fiscal_year_start", &value, &mut journal);
+
+        assert_eq!(result.is_ok(), expected.is_some());
+        assert_eq!(journal.header.fiscal_year_start, expected);
+    }
+
+    #[rstest::rstest]
+    #[case::string_commodity("default_commodity", ast::Value::String("JPY".to_string()))]
+    #[case::path_commodity("default_commodity", identifier("JPY/USD"))]
+    #[case::integer_bool("conversion_postings", ast::Value::Integer(1))]
+    #[case::integer_timezone("default_timezone", ast::Value::Integer(9))]
+    #[case::integer_title("title", ast::Value::Integer(1))]
+    #[case::string_integer("fiscal_year_start", ast::Value::String("4".to_string()))]
+    fn test_set_ill_typed_value(#[case] name: &str, #[case] value: ast::Value) {
+        let mut journal = output::Journal::default();
+        compile(name, &value, &mut journal).expect_err("Should have failed.");
+    }
 }
@@ -0,0 +1,180 @@
+//! Named constants, which are defined with `let` and used in amounts and `set` values.
+use super::{CompilationError, CompilationResult};
+use crate::{ast, utils};
+use std::collections::HashMap;
+
+/// The constants defined so far in a journal. A constant can only be used after its definition.
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    values: HashMap<String, i64>,
+}
+
+impl SymbolTable {
+    pub fn define(&mut self, name: &str, value: &ast::Expression) -> CompilationResult<()> {
+        if self.values.contains_key(name) {
+            return Err(CompilationError::GeneralError(format!(
+                "{name} is already defined."
+            )));
+        }
+
+        let value = self.evaluate(value)?;
+        self.values.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    /// Evaluates an amount expression. A division with a remainder must be inside a rounding function.
+    pub fn evaluate(&self, expression: &ast::Expression) -> CompilationResult<i64> {
+        self.evaluate_rounded(expression, None)
+    }
+
+    /// Evaluates an expression whose divisions are rounded with `rounding`, if it is inside a rounding function.
+    fn evaluate_rounded(
+        &self,
+        expression: &ast::Expression,
+        rounding: Option<ast::Rounding>,
+    ) -> CompilationResult<i64> {
+        let out_of_range = || CompilationError::AmountOutOfRange(expression.to_string());
+
+        match expression {
+            ast::Expression::Amount(x) => Ok(*x),
+            ast::Expression::Variable(x) => {
+                self.values.get(x).copied().ok_or_else(|| {
+                    CompilationError::GeneralError(format!("Undefined variable: {x}"))
+                })
+            }
+            ast::Expression::Parenthesized(x) => self.evaluate_rounded(x, rounding),
+            ast::Expression::Negation(x) => self
+                .evaluate_rounded(x, rounding)?
+                .checked_neg()
+                .ok_or_else(out_of_range),
+            ast::Expression::Rounded(rounding, x) => self.evaluate_rounded(x, Some(*rounding)),
+            ast::Expression::Binary(l, operator, r) => {
+                let (l, r) = (
+                    self.evaluate_rounded(l, rounding)?,
+                    self.evaluate_rounded(r, rounding)?,
+                );
+                let result = match operator {
+                    ast::Operator::Plus => l.checked_add(r),
+                    ast::Operator::Minus => l.checked_sub(r),
+                    ast::Operator::Times => l.checked_mul(r),
+                    ast::Operator::Divide if r == 0 => {
+                        return Err(CompilationError::GeneralError(format!(
+                            "Division by zero: {expression}"
+                        )))
+                    }
+                    ast::Operator::Divide => {
+                        let (l, r) = (l as i128, r as i128);
+                        let quotient = match rounding {
+                            _ if l % r == 0 => l / r,
+                            Some(ast::Rounding::Round) => utils::divide_rounded(l, r),
+                            Some(ast::Rounding::Floor) => utils::divide_floor(l, r),
+                            Some(ast::Rounding::Ceil) => utils::divide_ceil(l, r),
+                            None => {
+                                return Err(CompilationError::GeneralError(format!(
+                                    "{expression} has a remainder, so it must be rounded with round(...), floor(...) or ceil(...)."
+                                )))
+                            }
+                        };
+                        i64::try_from(quotient).ok()
+                    }
+                };
+                result.ok_or_else(out_of_range)
+            }
+        }
+    }
+
+    /// Returns the integer given as a `set` value, which is either written out or the name of a constant.
+    pub fn integer(&self, value: &ast::Value) -> Option<i64> {
+        match value {
+            ast::Value::Integer(x) => Some(*x),
+            ast::Value::Identifier(x) => self.values.get(x).copied(),
+            ast::Value::String(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn binary(l: ast::Expression, operator: ast::Operator, r: ast::Expression) -> ast::Expression {
+        ast::Expression::Binary(Box::new(l), operator, Box::new(r))
+    }
+
+    fn rounded(rounding: ast::Rounding, x: ast::Expression) -> ast::Expression {
+        ast::Expression::Rounded(rounding, Box::new(x))
+    }
+
+    fn variable(x: &str) -> ast::Expression {
+        ast::Expression::Variable(x.to_string())
+    }
+
+    #[rstest::rstest]
+    #[case::literal(1000.into(), Some(1000))]
+    #[case::times(binary(1200.into(), ast::Operator::Times, 3.into()), Some(3600))]
+    #[case::exact_divide(binary(1000.into(), ast::Operator::Divide, 8.into()), Some(125))]
+    #[case::divide_with_remainder(binary(1000.into(), ast::Operator::Divide, 3.into()), None)]
+    #[case::round(rounded(ast::Rounding::Round, binary((-5).into(), ast::Operator::Divide, 2.into())), Some(-3))]
+    #[case::floor(rounded(ast::Rounding::Floor, binary(1000.into(), ast::Operator::Divide, 3.into())), Some(333))]
+    #[case::ceil(rounded(ast::Rounding::Ceil, binary(1000.into(), ast::Operator::Divide, 3.into())), Some(334))]
+    #[case::rounds_every_division(
+        rounded(
+            ast::Rounding::Round,
+            binary(binary(1000.into(), ast::Operator::Divide, 3.into()), ast::Operator::Times, 3.into())
+        ),
+        Some(999)
+    )]
+    #[case::innermost_rounding(
+        rounded(
+            ast::Rounding::Floor,
+            rounded(ast::Rounding::Ceil, binary(1000.into(), ast::Operator::Divide, 3.into()))
+        ),
+        Some(334)
+    )]
+    #[case::negation(
+        ast::Expression::Negation(Box::new(binary(1.into(), ast::Operator::Minus, 3.into()))),
+        Some(2)
+    )]
+    #[case::division_by_zero(binary(1000.into(), ast::Operator::Divide, 0.into()), None)]
+    #[case::overflow(binary(i64::MAX.into(), ast::Operator::Plus, 1.into()), None)]
+    #[case::undefined_variable(variable("rent"), None)]
+    fn test_evaluate(#[case] expression: ast::Expression, #[case] expected: Option<i64>) {
+        let result = SymbolTable::default().evaluate(&expression);
+        assert_eq!(result.ok(), expected);
+    }
+
+    #[test]
+    fn test_define() {
+        let mut symbols = SymbolTable::default();
+        symbols.define("rent", &85000.into()).expect("Failed.");
+        symbols
+            .define(
+                "share",
+                &rounded(
+                    ast::Rounding::Round,
+                    binary(variable("rent"), ast::Operator::Divide, 3.into()),
+                ),
+            )
+            .expect("Failed.");
+
+        assert_eq!(
+            symbols.evaluate(&variable("share")).expect("Failed."),
+            28333
+        );
+        symbols
+            .define("rent", &90000.into())
+            .expect_err("Should have failed.");
+    }
+
+    #[rstest::rstest]
+    #[case::constant(ast::Value::Identifier("start".to_string()), Some(4))]
+    #[case::literal(ast::Value::Integer(10), Some(10))]
+    #[case::unknown(ast::Value::Identifier("JPY".to_string()), None)]
+    #[case::string(ast::Value::String("start".to_string()), None)]
+    fn test_integer(#[case] value: ast::Value, #[case] expected: Option<i64>) {
+        let mut symbols = SymbolTable::default();
+        symbols.define("start", &4.into()).expect("Failed.");
+
+        assert_eq!(symbols.integer(&value), expected);
+    }
+}
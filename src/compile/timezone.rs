@@ -0,0 +1,116 @@
+//! Resolving the timestamps written in the journal into absolute points in time.
+use super::{CompilationError, CompilationResult};
+use crate::{ast, output};
+
+use chrono::{FixedOffset, LocalResult, NaiveDateTime, TimeZone};
+
+/// The timezone applied to timestamps which do not specify an offset.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Timezone {
+    Fixed(FixedOffset),
+    Named(chrono_tz::Tz),
+}
+
+impl Timezone {
+    /// Parses either a fixed offset (e.g. `+09:00`) or an IANA timezone name (e.g. `Asia/Tokyo`).
+    pub fn parse(s: &str) -> CompilationResult<Self> {
+        if let Ok(offset) = s.parse::<FixedOffset>() {
+            return Ok(Timezone::Fixed(offset));
+        }
+
+        s.parse::<chrono_tz::Tz>()
+            .map(Timezone::Named)
+            .map_err(|_| CompilationError::GeneralError(format!("Invalid timezone: {s}")))
+    }
+
+    fn localize(&self, local: NaiveDateTime) -> LocalResult<output::TransactionTimestamp> {
+        match self {
+            Timezone::Fixed(offset) => offset.from_local_datetime(&local),
+            Timezone::Named(tz) => tz.from_local_datetime(&local).map(|x| x.fixed_offset()),
+        }
+    }
+}
+
+impl Default for Timezone {
+    fn default() -> Self {
+        Timezone::Fixed(FixedOffset::east_opt(0).unwrap())
+    }
+}
+
+/// Turns `ts` into an absolute timestamp.
+///
+/// An explicit offset always takes precedence. Otherwise, `timezone` is applied to the local time,
+/// which is midnight if the time was omitted. Local times which occur twice (e.g. at the end of
+/// daylight saving time) resolve to the earlier one, and local times which do not exist are an error.
+pub fn resolve(
+    ts: &ast::Timestamp,
+    timezone: &Timezone,
+) -> CompilationResult<output::TransactionTimestamp> {
+    let local = ts.naive_local();
+
+    let result = match ts.offset {
+        Some(offset) => offset.from_local_datetime(&local),
+        None => timezone.localize(local),
+    };
+
+    result.earliest().ok_or_else(|| {
+        CompilationError::GeneralError(format!("Timestamp does not exist in timezone: {ts}"))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{NaiveDate, NaiveTime};
+
+    fn timestamp(time: Option<(u32, u32)>, offset: Option<i32>) -> ast::Timestamp {
+        ast::Timestamp {
+            date: NaiveDate::from_ymd_opt(2026, 3, 8).unwrap(),
+            time: time.map(|(h, m)| NaiveTime::from_hms_opt(h, m, 0).unwrap()),
+            offset: offset.map(|x| FixedOffset::east_opt(x).unwrap()),
+        }
+    }
+
+    #[rstest::rstest]
+    #[case::date_in_utc(None, None, "UTC", "2026-03-08T00:00:00+00:00")]
+    #[case::date_in_fixed_offset(None, None, "+09:00", "2026-03-08T00:00:00+09:00")]
+    #[case::date_in_named_timezone(None, None, "Asia/Tokyo", "2026-03-08T00:00:00+09:00")]
+    #[case::time_in_named_timezone(Some((12, 30)), None, "Asia/Tokyo", "2026-03-08T12:30:00+09:00")]
+    #[case::explicit_offset_wins(Some((12, 30)), Some(0), "Asia/Tokyo", "2026-03-08T12:30:00+00:00")]
+    #[case::daylight_saving(Some((12, 0)), None, "America/New_York", "2026-03-08T12:00:00-04:00")]
+    fn test_resolve(
+        #[case] time: Option<(u32, u32)>,
+        #[case] offset: Option<i32>,
+        #[case] timezone: &str,
+        #[case] expected: &str,
+    ) {
+        let tz = Timezone::parse(timezone).expect("Invalid test case.");
+        let result = resolve(&timestamp(time, offset), &tz).expect("Failed.");
+        assert_eq!(result.to_rfc3339(), expected);
+    }
+
+    #[test]
+    fn test_ambiguous_time_resolves_to_earliest() {
+        let ts = ast::Timestamp {
+            date: NaiveDate::from_ymd_opt(2026, 11, 1).unwrap(),
+            time: Some(NaiveTime::from_hms_opt(1, 30, 0).unwrap()),
+            offset: None,
+        };
+        let tz = Timezone::parse("America/New_York").unwrap();
+        let result = resolve(&ts, &tz).expect("Failed.");
+        assert_eq!(result.to_rfc3339(), "2026-11-01T01:30:00-04:00");
+    }
+
+    #[test]
+    fn test_nonexistent_time_fails() {
+        let tz = Timezone::parse("America/New_York").unwrap();
+        resolve(&timestamp(Some((2, 30)), None), &tz).expect_err("Should have failed.");
+    }
+
+    #[rstest::rstest]
+    #[case::unknown_name("Asia/Atlantis")]
+    #[case::garbage("+9")]
+    fn test_invalid_timezone(#[case] timezone: &str) {
+        Timezone::parse(timezone).expect_err("Should have failed.");
+    }
+}
@@ -1,26 +1,42 @@
+use super::auto_rule::AutoRuleCompiler;
+use super::symbols::SymbolTable;
+use super::timezone::{self, Timezone};
 use super::{CompilationError, CompilationResult};
 use crate::ast;
 use crate::output;
 use std::collections::HashMap;
 
+/// The account used for the postings which balance an implicit conversion.
+const CONVERSION_ACCOUNT: &str = "equity/conversion";
+
 pub struct TransactionCompiler;
 
 impl TransactionCompiler {
-    fn compute_balances(postings: &[output::Posting]) -> HashMap<String, i64> {
+    fn compute_balances(postings: &[output::Posting]) -> CompilationResult<HashMap<String, i64>> {
         let mut m = HashMap::new();
 
         for p in postings.iter() {
             let commodity = &p.commodity;
             let balance = m.get(commodity).cloned().unwrap_or(0);
+            let balance = p.amount.checked_add(balance).ok_or_else(|| {
+                CompilationError::AmountOutOfRange(format!("the balance of {commodity}"))
+            })?;
 
-            m.insert(commodity.clone(), balance + p.amount);
+            m.insert(commodity.clone(), balance);
         }
 
-        m
+        Ok(m)
+    }
+
+    fn negate_balance(commodity: &str, amount: i64) -> CompilationResult<i64> {
+        amount
+            .checked_neg()
+            .ok_or_else(|| CompilationError::UnnegatableBalance(commodity.to_string()))
     }
 
     fn validate_postings(
         postings: &[ast::Posting],
+        symbols: &SymbolTable,
         journal: &output::Journal,
     ) -> CompilationResult<(Vec<output::Posting>, Option<usize>)> {
         use std::iter::repeat;
@@ -43,7 +59,20 @@ impl TransactionCompiler {
                 .commodity
                 .clone()
                 .unwrap_or(journal.header.default_commodity.clone());
-            p_out.amount = p_in.amount.unwrap_or(0);
+            p_out.amount = match &p_in.amount {
+                Some(x) => symbols.evaluate(x)?,
+                None => 0,
+            };
+            p_out.attributes = p_in.attributes.clone();
+            p_out.date = match p_in.date {
+                Some(ts) if ts.time.is_some() => {
+                    return Err(CompilationError::from_string(
+                        "A posting date cannot have a time.",
+                    ))
+                }
+                Some(ts) => Some(ts.date),
+                None => None,
+            };
             if p_in.amount.is_none() {
                 if i_empty_amount.is_some() {
                     return Err(CompilationError::from_string(
@@ -57,20 +86,75 @@ impl TransactionCompiler {
         Ok((out_postings, i_empty_amount))
     }
 
-    fn find_unbalanced_commodities(postings: &[output::Posting]) -> Vec<(String, i64)> {
-        Self::compute_balances(postings)
+    fn find_unbalanced_commodities(
+        postings: &[output::Posting],
+    ) -> CompilationResult<Vec<(String, i64)>> {
+        Ok(Self::compute_balances(postings)?
             .iter()
             .filter_map(|(k, v)| match v {
                 0 => None,
                 _ => Some((k.clone(), *v)),
             })
-            .collect()
+            .collect())
+    }
+
+    /// Infers the exchange between two unbalanced commodities, one of which must have been
+    /// spent (negative balance) and the other received (positive balance).
+    fn infer_conversion(
+        a: &(String, i64),
+        b: &(String, i64),
+    ) -> CompilationResult<output::Conversion> {
+        let ((from, from_amount), (to, to_amount)) = match (a.1 < 0, b.1 < 0) {
+            (true, false) => (a, b),
+            (false, true) => (b, a),
+            _ => {
+                return Err(CompilationError::from_string(
+                    "Unbalanced transaction. A conversion must have one negative and one positive commodity.",
+                ))
+            }
+        };
+
+        let from_amount = Self::negate_balance(from, *from_amount)?;
+        Ok(output::Conversion {
+            from_commodity: from.clone(),
+            from_amount,
+            to_commodity: to.clone(),
+            to_amount: *to_amount,
+            rate: *to_amount as f64 / from_amount as f64,
+        })
+    }
+
+    fn conversion_postings(conversion: &output::Conversion) -> [output::Posting; 2] {
+        [
+            output::Posting {
+                account: CONVERSION_ACCOUNT.to_string(),
+                commodity: conversion.from_commodity.clone(),
+                amount: conversion.from_amount,
+                generated: true,
+                ..Default::default()
+            },
+            output::Posting {
+                account: CONVERSION_ACCOUNT.to_string(),
+                commodity: conversion.to_commodity.clone(),
+                amount: -conversion.to_amount,
+                generated: true,
+                ..Default::default()
+            },
+        ]
     }
 
     fn ensure_transaction_is_balanced(t: &output::Transaction) -> CompilationResult<()> {
-        if Self::compute_balances(&t.postings)
+        // The two commodities of a conversion balance each other, unless conversion postings
+        // were added. Every other commodity must balance on its own.
+        let is_converted = |commodity: &str| {
+            t.conversion
+                .as_ref()
+                .is_some_and(|x| x.from_commodity == commodity || x.to_commodity == commodity)
+        };
+
+        if Self::compute_balances(&t.postings)?
             .iter()
-            .any(|(_, a)| *a != 0)
+            .any(|(c, a)| *a != 0 && !is_converted(c))
         {
             return Err(CompilationError::from_string("Unbalanced transaction."));
         }
@@ -78,7 +162,13 @@ impl TransactionCompiler {
         Ok(())
     }
 
-    pub fn compile(t: &ast::Transaction, journal: &mut output::Journal) -> CompilationResult<()> {
+    pub fn compile(
+        t: &ast::Transaction,
+        rules: &[ast::AutoRule],
+        symbols: &SymbolTable,
+        timezone: &Timezone,
+        journal: &mut output::Journal,
+    ) -> CompilationResult<()> {
         let n_postings = t.postings.len();
         if n_postings < 2 {
             return Err(CompilationError::from_string(
@@ -86,12 +176,24 @@ impl TransactionCompiler {
             ));
         }
 
-        let (mut postings, i_empty_amount) = Self::validate_postings(&t.postings, journal)?;
-        let unbalanced_commodities = Self::find_unbalanced_commodities(&postings);
-        if unbalanced_commodities.len() > 1 {
-            return Err(CompilationError::from_string(
-                "Only a single commodity can be unbalanced.",
-            ));
+        let (mut postings, i_empty_amount) =
+            Self::validate_postings(&t.postings, symbols, journal)?;
+        postings.extend(AutoRuleCompiler::apply(rules, t, symbols, journal)?);
+        let mut unbalanced_commodities = Self::find_unbalanced_commodities(&postings)?;
+        // Balances are computed in a HashMap, so we sort them to get a deterministic conversion.
+        unbalanced_commodities.sort();
+
+        let conversion = match (unbalanced_commodities.as_slice(), i_empty_amount) {
+            ([a, b], None) => Some(Self::infer_conversion(a, b)?),
+            (x, _) if x.len() > 1 => return Err(CompilationError::from_string(
+                "Only a single commodity can be unbalanced, or two if there is no empty amount.",
+            )),
+            _ => None,
+        };
+        if let Some(conversion) = &conversion {
+            if journal.header.conversion_postings {
+                postings.extend(Self::conversion_postings(conversion));
+            }
         }
 
         let unbalanced_commodity = unbalanced_commodities.first().cloned();
@@ -107,15 +209,21 @@ impl TransactionCompiler {
                 )));
             }
 
-            posting.amount = -amount;
+            posting.amount = Self::negate_balance(&commodity, amount)?;
         }
 
         let out_t = output::Transaction {
             header: output::TransactionHeader {
-                timestamp: t.header.timestamp,
+                timestamp: timezone::resolve(&t.header.timestamp, timezone)?,
+                status: t.header.status.map(|x| match x {
+                    ast::TransactionStatus::Pending => output::TransactionStatus::Pending,
+                    ast::TransactionStatus::Cleared => output::TransactionStatus::Cleared,
+                }),
+                description: t.header.description.clone(),
                 attributes: t.header.attributes.clone(),
             },
             postings,
+            conversion,
         };
 
         Self::ensure_transaction_is_balanced(&out_t)?;
@@ -128,33 +236,47 @@ impl TransactionCompiler {
 
 #[cfg(test)]
 mod test {
-    use super::super::CompilationResult;
+    use super::super::{CompilationError, CompilationResult};
+    use super::Timezone;
     use crate::{ast, output};
+    use proptest::prelude::*;
 
     fn compile_transaction(
         t: &ast::Transaction,
         journal: &mut output::Journal,
     ) -> CompilationResult<()> {
-        super::TransactionCompiler::compile(&t, journal)
+        super::TransactionCompiler::compile(
+            t,
+            &[],
+            &Default::default(),
+            &Default::default(),
+            journal,
+        )
+    }
+
+    fn header() -> ast::TransactionHeader {
+        ast::TransactionHeader::new(
+            chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05.000+09:00")
+                .unwrap()
+                .into(),
+        )
     }
 
     fn sample_transaction() -> ast::Transaction {
         ast::Transaction {
-            header: ast::TransactionHeader {
-                timestamp: chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05.000+09:00")
-                    .unwrap(),
-                attributes: serde_yaml::Mapping::default(),
-            },
+            header: header(),
             postings: vec![
                 ast::Posting {
                     account: "foo".to_string(),
                     commodity: Some("JPY".to_string()),
-                    amount: Some(1000),
+                    amount: Some(1000.into()),
+                    ..Default::default()
                 },
                 ast::Posting {
                     account: "bar".to_string(),
                     commodity: Some("JPY".to_string()),
-                    amount: Some(-1000),
+                    amount: Some((-1000).into()),
+                    ..Default::default()
                 },
             ],
         }
@@ -167,15 +289,166 @@ mod test {
         compile_transaction(&t, &mut journal).expect("Failed.");
 
         let j_t = journal.transactions.first().expect("Failed.");
-        assert_eq!(j_t.header.timestamp, t.header.timestamp);
+        assert_eq!(
+            ast::Timestamp::from(j_t.header.timestamp),
+            t.header.timestamp
+        );
         assert_eq!(j_t.postings.len(), t.postings.len());
         for (p_out, p_in) in j_t.postings.iter().zip(t.postings.iter()) {
             assert_eq!(p_out.account, p_in.account);
             assert_eq!(p_out.commodity, p_in.commodity.clone().unwrap());
-            assert_eq!(p_out.amount, p_in.amount.clone().unwrap());
+            assert_eq!(Some(p_out.amount.into()), p_in.amount);
         }
     }
 
+    #[test]
+    fn test_applies_default_timezone() {
+        let mut t = sample_transaction();
+        t.header.timestamp.time = None;
+        t.header.timestamp.offset = None;
+        let timezone = Timezone::parse("Asia/Tokyo").expect("Invalid test case.");
+        let mut journal = output::Journal::default();
+        super::TransactionCompiler::compile(&t, &[], &Default::default(), &timezone, &mut journal)
+            .expect("Failed.");
+
+        let j_t = journal.transactions.first().expect("Failed.");
+        assert_eq!(
+            j_t.header.timestamp.to_rfc3339(),
+            "2026-01-02T00:00:00+09:00"
+        );
+    }
+
+    #[test]
+    fn test_carries_status_and_description() {
+        let mut t = sample_transaction();
+        t.header.status = Some(ast::TransactionStatus::Pending);
+        t.header.description = Some("Coffee shop".to_string());
+        let mut journal = output::Journal::default();
+        compile_transaction(&t, &mut journal).expect("Failed.");
+
+        let header = &journal.transactions[0].header;
+        assert_eq!(header.status, Some(output::TransactionStatus::Pending));
+        assert_eq!(header.description.as_deref(), Some("Coffee shop"));
+    }
+
+    #[test]
+    fn test_carries_posting_date_and_attributes() {
+        let mut t = sample_transaction();
+        t.postings[0]
+            .attributes
+            .insert("receipt".into(), "123".into());
+        t.postings[0].date = Some(ast::Timestamp {
+            date: chrono::NaiveDate::from_ymd_opt(2026, 1, 5).unwrap(),
+            time: None,
+            offset: None,
+        });
+        let mut journal = output::Journal::default();
+        compile_transaction(&t, &mut journal).expect("Failed.");
+
+        let p = &journal.transactions[0].postings[0];
+        assert_eq!(p.attributes, t.postings[0].attributes);
+        assert_eq!(p.date, chrono::NaiveDate::from_ymd_opt(2026, 1, 5));
+    }
+
+    #[test]
+    fn test_posting_date_with_time_fails() {
+        let mut t = sample_transaction();
+        t.postings[0].date = Some(t.header.timestamp);
+        let mut journal = output::Journal::default();
+        compile_transaction(&t, &mut journal).expect_err("Should have failed.");
+    }
+
+    fn conversion_transaction() -> ast::Transaction {
+        let mut t = sample_transaction();
+        t.postings[0].commodity = Some("USD".to_string());
+        t.postings[0].amount = Some((-100).into());
+        t.postings[1].commodity = Some("JPY".to_string());
+        t.postings[1].amount = Some(15000.into());
+        t
+    }
+
+    #[test]
+    fn test_infers_conversion() {
+        let mut journal = output::Journal::default();
+        compile_transaction(&conversion_transaction(), &mut journal).expect("Failed.");
+
+        let j_t = &journal.transactions[0];
+        assert_eq!(j_t.postings.len(), 2);
+        assert_eq!(
+            j_t.conversion,
+            Some(output::Conversion {
+                from_commodity: "USD".to_string(),
+                from_amount: 100,
+                to_commodity: "JPY".to_string(),
+                to_amount: 15000,
+                rate: 150.0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_adds_conversion_postings() {
+        let mut journal = output::Journal::default();
+        journal.header.conversion_postings = true;
+        compile_transaction(&conversion_transaction(), &mut journal).expect("Failed.");
+
+        let postings = &journal.transactions[0].postings;
+        assert_eq!(postings.len(), 4);
+        assert_eq!(
+            postings[2..]
+                .iter()
+                .map(|p| (p.account.as_str(), p.commodity.as_str(), p.amount))
+                .collect::<Vec<_>>(),
+            vec![
+                ("equity/conversion", "USD", 100),
+                ("equity/conversion", "JPY", -15000)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_conversion_with_empty_amount_fails() {
+        let mut t = conversion_transaction();
+        t.postings.push(ast::Posting {
+            account: "baz".to_string(),
+            commodity: Some("JPY".to_string()),
+            amount: None,
+            ..Default::default()
+        });
+        let mut journal = output::Journal::default();
+        compile_transaction(&t, &mut journal).expect_err("Should have failed.");
+    }
+
+    #[test]
+    fn test_three_unbalanced_commodities_fail() {
+        let mut t = conversion_transaction();
+        t.postings.push(ast::Posting {
+            account: "baz".to_string(),
+            commodity: Some("EUR".to_string()),
+            amount: Some(5.into()),
+            ..Default::default()
+        });
+        let mut journal = output::Journal::default();
+        compile_transaction(&t, &mut journal).expect_err("Should have failed.");
+    }
+
+    #[test]
+    fn test_conversion_only_exempts_converted_commodities() {
+        let mut journal = output::Journal::default();
+        compile_transaction(&conversion_transaction(), &mut journal).expect("Failed.");
+        let mut t = journal.transactions[0].clone();
+        super::TransactionCompiler::ensure_transaction_is_balanced(&t).expect("Failed.");
+
+        t.postings.push(output::Posting {
+            account: "baz".to_string(),
+            commodity: "EUR".to_string(),
+            amount: 5,
+            ..Default::default()
+        });
+        super::TransactionCompiler::ensure_transaction_is_balanced(&t)
+            .expect_err("Should have failed.");
+    }
+
     #[test]
     fn test_substitutes_empty_commodity_for_default() {
         let mut t = sample_transaction();
@@ -192,6 +465,38 @@ mod test {
         assert_eq!(j_t.postings[0].commodity, "JPY".to_string());
     }
 
+    fn binary(l: i64, operator: ast::Operator, r: i64) -> ast::Expression {
+        ast::Expression::Binary(Box::new(l.into()), operator, Box::new(r.into()))
+    }
+
+    #[test]
+    fn test_exports_evaluated_amounts() {
+        let mut journal = output::Journal::default();
+        let mut t = sample_transaction();
+        t.postings[0].amount = Some(ast::Expression::Rounded(
+            ast::Rounding::Round,
+            Box::new(binary(1000, ast::Operator::Divide, 3)),
+        ));
+        t.postings[1].amount = None;
+        compile_transaction(&t, &mut journal).expect("Failed.");
+
+        let amounts: Vec<i64> = journal.transactions[0]
+            .postings
+            .iter()
+            .map(|p| p.amount)
+            .collect();
+        assert_eq!(amounts, vec![333, -333]);
+    }
+
+    #[test]
+    fn test_rejects_division_with_remainder() {
+        let mut journal = output::Journal::default();
+        let mut t = sample_transaction();
+        t.postings[0].amount = Some(binary(1000, ast::Operator::Divide, 3));
+        t.postings[1].amount = None;
+        compile_transaction(&t, &mut journal).expect_err("Should have failed.");
+    }
+
     #[test]
     fn test_auto_balances_single_missing_amount() {
         let mut t = sample_transaction();
@@ -205,6 +510,149 @@ mod test {
         assert_eq!(j_t.postings[0].amount, 1000);
     }
 
+    fn tax_rule(attributes: serde_yaml::Mapping) -> ast::AutoRule {
+        ast::AutoRule {
+            account: Some("foo".to_string()),
+            attributes,
+            postings: vec![
+                ast::AutoPosting {
+                    account: "tax".to_string(),
+                    commodity: None,
+                    amount: ast::AutoAmount::Percentage(1000),
+                },
+                ast::AutoPosting {
+                    account: "foo".to_string(),
+                    commodity: None,
+                    amount: ast::AutoAmount::Percentage(-1000),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_applies_auto_rules_before_balancing() {
+        let mut t = sample_transaction();
+        t.postings[1].amount = None;
+
+        let mut journal = output::Journal::default();
+        super::TransactionCompiler::compile(
+            &t,
+            &[tax_rule(Default::default())],
+            &Default::default(),
+            &Default::default(),
+            &mut journal,
+        )
+        .expect("Failed.");
+
+        let postings = &journal.transactions[0].postings;
+        assert_eq!(
+            postings
+                .iter()
+                .map(|p| (p.account.as_str(), p.amount, p.generated))
+                .collect::<Vec<_>>(),
+            vec![
+                ("foo", 1000, false),
+                ("bar", -1000, false),
+                ("tax", 100, true),
+                ("foo", -100, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_auto_rule_filters_by_attributes() {
+        let mut attributes = serde_yaml::Mapping::default();
+        attributes.insert("foo".into(), "bar".into());
+
+        let mut journal = output::Journal::default();
+        super::TransactionCompiler::compile(
+            &sample_transaction(),
+            &[tax_rule(attributes)],
+            &Default::default(),
+            &Default::default(),
+            &mut journal,
+        )
+        .expect("Failed.");
+
+        assert_eq!(journal.transactions[0].postings.len(), 2);
+    }
+
+    #[test]
+    fn test_auto_rule_without_account_applies_once() {
+        let mut attributes = serde_yaml::Mapping::default();
+        attributes.insert("foo".into(), "bar".into());
+        let rule = ast::AutoRule {
+            account: None,
+            attributes,
+            postings: vec![
+                ast::AutoPosting {
+                    account: "fee".to_string(),
+                    commodity: None,
+                    amount: ast::AutoAmount::Fixed(ast::Expression::Amount(10)),
+                },
+                ast::AutoPosting {
+                    account: "bar".to_string(),
+                    commodity: None,
+                    amount: ast::AutoAmount::Fixed(ast::Expression::Amount(-10)),
+                },
+            ],
+        };
+        let mut t = sample_transaction();
+        t.header.attributes.insert("foo".into(), "bar".into());
+
+        let mut journal = output::Journal::default();
+        let rules = [rule];
+        super::TransactionCompiler::compile(
+            &t,
+            &rules,
+            &Default::default(),
+            &Default::default(),
+            &mut journal,
+        )
+        .expect("Failed.");
+        super::TransactionCompiler::compile(
+            &sample_transaction(),
+            &rules,
+            &Default::default(),
+            &Default::default(),
+            &mut journal,
+        )
+        .expect("Failed.");
+
+        let accounts = |t: &output::Transaction| {
+            t.postings
+                .iter()
+                .map(|p| (p.account.clone(), p.amount))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            accounts(&journal.transactions[0]),
+            vec![
+                ("foo".to_string(), 1000),
+                ("bar".to_string(), -1000),
+                ("fee".to_string(), 10),
+                ("bar".to_string(), -10)
+            ]
+        );
+        assert_eq!(accounts(&journal.transactions[1]).len(), 2);
+    }
+
+    #[test]
+    fn test_auto_rule_percentage_of_empty_amount_fails() {
+        let mut t = sample_transaction();
+        t.postings[0].amount = None;
+
+        let mut journal = output::Journal::default();
+        super::TransactionCompiler::compile(
+            &t,
+            &[tax_rule(Default::default())],
+            &Default::default(),
+            &Default::default(),
+            &mut journal,
+        )
+        .expect_err("Should have failed.");
+    }
+
     #[test]
     fn test_attributes_are_copied() {
         let mut t = sample_transaction();
@@ -224,112 +672,98 @@ mod test {
     #[rstest::rstest]
     #[case::with_0_postings(
         ast::Transaction {
-            header: ast::TransactionHeader {
-                timestamp: chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05.000+09:00")
-                    .unwrap(),
-                attributes: serde_yaml::Mapping::default(),
-            },
+            header: header(),
             postings: vec![],
         }
     )]
     #[case::with_1_posting(
         ast::Transaction {
-            header: ast::TransactionHeader {
-                timestamp: chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05.000+09:00")
-                    .unwrap(),
-                attributes: serde_yaml::Mapping::default(),
-            },
+            header: header(),
             postings: vec![
                 ast::Posting {
                     account: "foo".to_string(),
                     commodity: None,
-                    amount: None
+                    amount: None,
+                    ..Default::default()
                 }
             ],
         }
     )]
     #[case::with_net_negative_amounts(
         ast::Transaction {
-            header: ast::TransactionHeader {
-                timestamp: chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05.000+09:00")
-                    .unwrap(),
-                attributes: serde_yaml::Mapping::default(),
-            },
+            header: header(),
             postings: vec![
                 ast::Posting {
                     account: "foo".to_string(),
                     commodity: None,
-                    amount: Some(0)
+                    amount: Some(0.into()),
+                    ..Default::default()
                 },
                 ast::Posting {
                     account: "bar".to_string(),
                     commodity: None,
-                    amount: Some(-1000)
+                    amount: Some((-1000).into()),
+                    ..Default::default()
                 }
             ],
         })]
     #[case::with_net_positive_amounts(
         ast::Transaction {
-            header: ast::TransactionHeader {
-                timestamp: chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05.000+09:00")
-                    .unwrap(),
-                attributes: serde_yaml::Mapping::default(),
-            },
+            header: header(),
             postings: vec![
                 ast::Posting {
                     account: "foo".to_string(),
                     commodity: None,
-                    amount: Some(0)
+                    amount: Some(0.into()),
+                    ..Default::default()
                 },
                 ast::Posting {
                     account: "bar".to_string(),
                     commodity: None,
-                    amount: Some(1000)
+                    amount: Some(1000.into()),
+                    ..Default::default()
                 }
             ],
         })]
     #[case::with_multiple_empty_amounts(
         ast::Transaction {
-            header: ast::TransactionHeader {
-                timestamp: chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05.000+09:00")
-                    .unwrap(),
-                attributes: serde_yaml::Mapping::default(),
-            },
+            header: header(),
             postings: vec![
                 ast::Posting {
                     account: "foo".to_string(),
                     commodity: None,
-                    amount: Some(1000)
+                    amount: Some(1000.into()),
+                    ..Default::default()
                 },
                 ast::Posting {
                     account: "bar".to_string(),
                     commodity: None,
-                    amount: None
+                    amount: None,
+                    ..Default::default()
                 },
                 ast::Posting {
                     account: "bar".to_string(),
                     commodity: None,
-                    amount: None
+                    amount: None,
+                    ..Default::default()
                 }
             ],
         })]
-    #[case::with_unbalanced_commodities(
+    #[case::with_unbalanced_commodities_of_same_sign(
         ast::Transaction {
-            header: ast::TransactionHeader {
-                timestamp: chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05.000+09:00")
-                    .unwrap(),
-                attributes: serde_yaml::Mapping::default(),
-            },
+            header: header(),
             postings: vec![
                 ast::Posting {
                     account: "foo".to_string(),
                     commodity: Some("USD".to_string()),
-                    amount: Some(1000)
+                    amount: Some(1000.into()),
+                    ..Default::default()
                 },
                 ast::Posting {
                     account: "bar".to_string(),
                     commodity: Some("JPY".to_string()),
-                    amount: Some(-1000)
+                    amount: Some(1000.into()),
+                    ..Default::default()
                 },
             ],
         })]
@@ -338,4 +772,76 @@ mod test {
 
         compile_transaction(&t, &mut journal).expect_err("Should have failed.");
     }
+
+    #[rstest::rstest]
+    #[case::sum_overflows(vec![Some(i64::MAX), Some(1), None])]
+    #[case::sum_underflows(vec![Some(i64::MIN), Some(-1), None])]
+    fn test_rejects_balance_out_of_range(#[case] amounts: Vec<Option<i64>>) {
+        let t = transaction_with_amounts(&amounts);
+        let result = compile_transaction(&t, &mut output::Journal::default());
+        assert!(matches!(result, Err(CompilationError::AmountOutOfRange(_))));
+    }
+
+    #[test]
+    fn test_rejects_unnegatable_empty_amount() {
+        let t = transaction_with_amounts(&[Some(i64::MIN + 1), Some(-1), None]);
+        let result = compile_transaction(&t, &mut output::Journal::default());
+        assert!(matches!(
+            result,
+            Err(CompilationError::UnnegatableBalance(x)) if x == "JPY"
+        ));
+    }
+
+    fn transaction_with_amounts(amounts: &[Option<i64>]) -> ast::Transaction {
+        let mut t = sample_transaction();
+        t.postings = amounts
+            .iter()
+            .map(|x| ast::Posting {
+                amount: x.map(ast::Expression::from),
+                ..t.postings[0].clone()
+            })
+            .collect();
+        t
+    }
+
+    fn amount_strategy() -> impl Strategy<Value = i64> {
+        prop_oneof![Just(i64::MIN), Just(i64::MAX), -1000i64..1000, any::<i64>(),]
+    }
+
+    proptest! {
+        #[test]
+        fn test_balancing_never_overflows(amounts in prop::collection::vec(amount_strategy(), 1..5)) {
+            let mut postings: Vec<Option<i64>> = amounts.iter().copied().map(Some).collect();
+            postings.push(None);
+            let t = transaction_with_amounts(&postings);
+            let mut journal = output::Journal::default();
+
+            let fits = |x: i128| i64::try_from(x).is_ok();
+            let partial_sums: Vec<i128> = amounts
+                .iter()
+                .scan(0i128, |sum, x| {
+                    *sum += *x as i128;
+                    Some(*sum)
+                })
+                .collect();
+            let balanceable = partial_sums.iter().all(|x| fits(*x))
+                && fits(-partial_sums.last().copied().unwrap_or(0));
+
+            match compile_transaction(&t, &mut journal) {
+                Ok(()) => {
+                    prop_assert!(balanceable);
+                    let total: i128 = journal.transactions[0]
+                        .postings
+                        .iter()
+                        .map(|p| p.amount as i128)
+                        .sum();
+                    prop_assert_eq!(total, 0);
+                }
+                Err(CompilationError::AmountOutOfRange(_) | CompilationError::UnnegatableBalance(_)) => {
+                    prop_assert!(!balanceable);
+                }
+                Err(e) => prop_assert!(false, "Unexpected error: {e}"),
+            }
+        }
+    }
 }
@@ -13,6 +13,7 @@ mod amount;
 mod basic;
 mod core;
 mod error;
+mod expression;
 mod identifier;
 mod timestamp;
 mod whitespace;
@@ -20,6 +21,7 @@ mod whitespace;
 use core::{LexerResult, NomResult, StringScanner};
 
 pub use error::{LexerError, LexerErrorDetails};
+pub use identifier::is_identifier;
 
 pub type TokenLocation = usize;
 
@@ -40,47 +42,110 @@ impl DecoratedToken {
     }
 }
 
-fn compare_match_length(
-    a: &(StringScanner, Token),
-    b: &(StringScanner, Token),
-) -> std::cmp::Ordering {
-    a.0.as_str().len().cmp(&b.0.as_str().len())
+type Lexer = fn(StringScanner) -> NomResult<Token>;
+
+/// The lexers which can match at a position starting with `c`, in the order in which they win ties.
+/// Lexers which absorb leading whitespace can also start with a space or a tab, and a description
+/// can start with anything but a newline.
+fn lexers_for(c: char) -> &'static [Lexer] {
+    match c {
+        ' ' | '\t' => &[
+            basic::lex_whitespace,
+            timestamp::lex,
+            basic::lex_status,
+            basic::lex_description,
+            amount::lex,
+            amount::lex_percentage,
+            expression::lex_operator,
+            expression::lex_left_parenthesis,
+            expression::lex_right_parenthesis,
+        ],
+        '\n' => &[basic::lex_line_separator],
+        '/' => &[
+            basic::lex_comment,
+            basic::lex_description,
+            expression::lex_operator,
+            basic::lex_account_separator,
+        ],
+        '"' => &[basic::lex_string, basic::lex_description],
+        '*' => &[
+            basic::lex_status,
+            basic::lex_description,
+            expression::lex_operator,
+        ],
+        '!' => &[basic::lex_status, basic::lex_description],
+        '+' | '-' => &[
+            basic::lex_description,
+            amount::lex,
+            amount::lex_percentage,
+            expression::lex_operator,
+        ],
+        '0'..='9' => &[
+            timestamp::lex,
+            basic::lex_description,
+            amount::lex,
+            amount::lex_percentage,
+        ],
+        '(' => &[basic::lex_description, expression::lex_left_parenthesis],
+        ')' => &[basic::lex_description, expression::lex_right_parenthesis],
+        ';' => &[basic::lex_description, basic::lex_posting_separator],
+        '=' => &[basic::lex_description, basic::lex_equals],
+        'a'..='z' | 'A'..='Z' => &[basic::lex_keyword, identifier::lex, basic::lex_description],
+        _ => &[identifier::lex, basic::lex_description],
+    }
 }
 
 fn lex_single_token(input: StringScanner) -> NomResult<Token> {
-    // First we lexer in order of priority
-    for mut lexer in [basic::lex_yaml_matter, basic::lex_indent] {
-        if let Ok(x) = lexer.parse(input.clone()) {
-            return Ok(x);
+    let Some(c) = input.as_str().chars().next() else {
+        return Err(core::error_at(
+            input.location(),
+            LexerErrorDetails::NothingMatched,
+        ));
+    };
+
+    // First we lexer in order of priority. Both of them start with an indent.
+    if c == ' ' {
+        for mut lexer in [basic::lex_yaml_matter, basic::lex_indent] {
+            if let Ok(x) = lexer.parse(input.clone()) {
+                return Ok(x);
+            }
         }
     }
 
     // If none of the priority lexers succeed,
     // we take the longest match of the remaining ones.
-
-    let mut results = vec![];
-    for mut lexer in [
-        basic::lex_whitespace,
-        basic::lex_comment,
-        basic::lex_keyword,
-        identifier::lex,
-        timestamp::lex,
-        amount::lex,
-        basic::lex_account_separator,
-        basic::lex_posting_separator,
-        basic::lex_line_separator,
-    ] {
-        if let Ok(x) = lexer.parse(input.clone()) {
-            results.push(x);
+    let mut result: Option<(StringScanner, Token)> = None;
+    let mut failure = None;
+    for mut lexer in lexers_for(c).iter().copied() {
+        match lexer.parse(input.clone()) {
+            // Note: on a tie, the lexer which comes first wins.
+            Ok(x)
+                if result
+                    .as_ref()
+                    .map_or(true, |r| x.0.location() > r.0.location()) =>
+            {
+                result = Some(x)
+            }
+            Ok(_) => {}
+            // Keep the first error which says more than that the lexer did not apply.
+            Err(nom::Err::Failure(e))
+                if failure.is_none()
+                    && !matches!(
+                        e.details,
+                        LexerErrorDetails::InternalError(_) | LexerErrorDetails::NomError(_)
+                    ) =>
+            {
+                failure = Some(e)
+            }
+            Err(_) => {}
         }
     }
 
-    // Note: we rely on preserving the initial order here.
-    results.sort_by(compare_match_length);
-
-    let Some(result) = results.first().cloned() else {
+    let Some(result) = result else {
+        if let Some(e) = failure {
+            return Err(nom::Err::Failure(e));
+        }
         return Err(core::error_at(
-            &input,
             input.location(),
             LexerErrorDetails::NothingMatched,
         ));
@@ -91,50 +156,46 @@ fn lex_single_token(input: StringScanner) -> NomResult<Token> {
 
 fn nom_lex_string(input: StringScanner) -> LexerResult<Vec<DecoratedToken>> {
     let content = input.content.clone();
-    let (input, _) = opt(whitespace::linespace)
-        .parse(input)
-        .map_err(|e| match e {
-            nom::Err::Incomplete(_) => LexerError {
-                content: content.clone(),
-                location: 0,
-                details: LexerErrorDetails::InternalError("incomplete".to_string()),
-                previous_tokens: vec![],
-            },
-            nom::Err::Error(e1) => e1,
-            nom::Err::Failure(e1) => e1,
-        })?;
+    let (input, _) = opt(whitespace::linespace).parse(input).map_err(|e| {
+        let (location, details) = match e {
+            nom::Err::Incomplete(_) => (0, LexerErrorDetails::InternalError("incomplete")),
+            nom::Err::Error(e1) | nom::Err::Failure(e1) => (e1.location, e1.details),
+        };
+        LexerError {
+            content: content.clone(),
+            location,
+            details,
+            previous_tokens: vec![],
+        }
+    })?;
 
     let mut remaining = input;
 
     let mut tokens = vec![];
+    let mut interner = identifier::Interner::default();
     loop {
         if remaining.is_empty() {
             break;
         }
 
         let loc = remaining.location();
-        let (rest, t) = lex_single_token(remaining).map_err(|e| match e {
-            nom::Err::Incomplete(_) => LexerError {
-                content: content.clone(),
-                location: loc,
-                details: LexerErrorDetails::InternalError("incomplete".to_string()),
-                previous_tokens: tokens.clone(),
-            },
-            nom::Err::Error(e1) => LexerError {
+        let (rest, t) = lex_single_token(remaining).map_err(|e| {
+            let details = match e {
+                nom::Err::Incomplete(_) => LexerErrorDetails::InternalError("incomplete"),
+                nom::Err::Error(e1) | nom::Err::Failure(e1) => e1.details,
+            };
+            // The error only shows the last few tokens, so there is no need to copy all of them.
+            let n_previous = tokens.len().min(error::PREVIOUS_TOKENS + 1);
+            LexerError {
                 content: content.clone(),
                 location: loc,
-                details: e1.details,
-                previous_tokens: tokens.clone(),
-            },
-            nom::Err::Failure(e1) => LexerError {
-                content: content.clone(),
-                location: loc,
-                details: e1.details,
-                previous_tokens: tokens.clone(),
-            },
+                details,
+                previous_tokens: tokens[tokens.len() - n_previous..].to_vec(),
+            }
         })?;
         remaining = rest;
 
+        let t = interner.intern(t);
         tokens.push(DecoratedToken::new(t.clone(), loc));
         remaining.set_last_token(t);
     }
@@ -152,8 +213,13 @@ pub fn lex_string(content: &str) -> LexerResult<Vec<DecoratedToken>> {
 
 #[cfg(test)]
 mod test {
-    use super::*;
-    use crate::tokens::Token;
+    use super::{
+        amount, basic, expression, identifier, lex_single_token, lex_string, timestamp, Lexer,
+        LexerErrorDetails, StringScanner,
+    };
+    use crate::tokens::{Token, TransactionStatus};
+    use nom::Parser;
+    use proptest::prelude::*;
 
     #[test]
     fn test_inserts_eof_token_at_end() {
@@ -179,7 +245,53 @@ mod test {
         let Token::Identifier(x) = &tok.token() else {
             panic!("Should have been an identifier token.");
         };
-        assert_eq!(x, "foo");
+        assert_eq!(x.as_ref(), "foo");
+    }
+
+    #[test]
+    fn test_lexes_posting_date() {
+        let input = "2026-01-01\n  a ; JPY ; -1000 ; 2026-01-05\n";
+        let tokens = lex_string(input).expect("Failed.");
+        let the_tokens: Vec<Token> = tokens
+            .iter()
+            .map(|x| x.token().clone())
+            .filter(|x| !matches!(x, Token::Whitespace))
+            .collect();
+        assert!(matches!(
+            the_tokens.as_slice(),
+            [
+                Token::Timestamp(_),
+                Token::LineSeparator,
+                Token::Indent,
+                Token::Identifier(_),
+                Token::PostingSeparator,
+                Token::Identifier(_),
+                Token::PostingSeparator,
+                Token::Amount(-1000),
+                Token::PostingSeparator,
+                Token::Timestamp(_),
+                Token::LineSeparator,
+                Token::Eof,
+            ]
+        ));
+    }
+
+    #[rstest::rstest]
+    #[case::date_only("2026-01-01\n", vec![])]
+    #[case::status("2026-01-01 *\n", vec![Token::Status(TransactionStatus::Cleared)])]
+    #[case::description(
+        "2026-01-01 12:30 Coffee shop // comment\n",
+        vec![Token::Description("Coffee shop".into()), Token::Whitespace, Token::Comment(" comment".into())]
+    )]
+    #[case::status_and_description(
+        "2026-01-01 ! Coffee shop\n",
+        vec![Token::Status(TransactionStatus::Pending), Token::Description("Coffee shop".into())]
+    )]
+    fn test_lexes_transaction_header(#[case] input: &str, #[case] expected: Vec<Token>) {
+        let tokens = lex_string(input).expect("Failed.");
+        let the_tokens: Vec<Token> = tokens.iter().map(|x| x.token().clone()).collect();
+        assert!(matches!(the_tokens[0], Token::Timestamp(_)));
+        assert_eq!(the_tokens[1..the_tokens.len() - 2], expected);
     }
 
     #[test]
@@ -190,15 +302,178 @@ mod test {
         assert_eq!(
             the_tokens,
             vec![
-                Token::Identifier("foo".to_string()),
+                Token::Identifier("foo".into()),
                 Token::Whitespace,
                 Token::LineSeparator,
                 Token::LineSeparator,
-                Token::Identifier("bar".to_string()),
+                Token::Identifier("bar".into()),
                 Token::LineSeparator,
                 Token::LineSeparator,
                 Token::Eof
             ]
         );
     }
+
+    #[test]
+    fn test_lexes_keywords_only_at_start_of_line() {
+        let input = "auto auto\n  auto ; ; 10%\n";
+        let tokens = lex_string(input).expect("Failed.");
+        let the_tokens: Vec<Token> = tokens
+            .iter()
+            .map(|x| x.token().clone())
+            .filter(|x| !matches!(x, Token::Whitespace))
+            .collect();
+        assert!(matches!(
+            the_tokens.as_slice(),
+            [
+                Token::Keyword(crate::tokens::Keyword::Auto),
+                Token::Identifier(a),
+                Token::LineSeparator,
+                Token::Indent,
+                Token::Identifier(b),
+                Token::PostingSeparator,
+                Token::PostingSeparator,
+                Token::Percentage(1000),
+                Token::LineSeparator,
+                Token::Eof,
+            ] if a.as_ref() == "auto" && b.as_ref() == "auto"
+        ));
+    }
+
+    #[test]
+    fn test_lexes_amount_expression() {
+        use crate::tokens::Operator;
+
+        let input = "  a ; ; -((1200 * 3) + 450) - 1,000/3\n";
+        let tokens = lex_string(input).expect("Failed.");
+        let the_tokens: Vec<Token> = tokens.iter().map(|x| x.token().clone()).collect();
+        assert_eq!(
+            the_tokens[6..the_tokens.len() - 2],
+            [
+                Token::Operator(Operator::Minus),
+                Token::LeftParenthesis,
+                Token::LeftParenthesis,
+                Token::Amount(1200),
+                Token::Operator(Operator::Times),
+                Token::Amount(3),
+                Token::RightParenthesis,
+                Token::Operator(Operator::Plus),
+                Token::Amount(450),
+                Token::RightParenthesis,
+                Token::Operator(Operator::Minus),
+                Token::Amount(1000),
+                Token::Operator(Operator::Divide),
+                Token::Amount(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reports_amount_out_of_range() {
+        let input = "  a ; ; 10000000000000000000\n";
+        let e = lex_string(input).expect_err("Should have failed.");
+        assert!(matches!(e.details, LexerErrorDetails::AmountOutOfRange));
+        assert_eq!(e.location, 8);
+    }
+
+    #[test]
+    fn test_interns_identifiers() {
+        let tokens = lex_string("2026-01-01\n  cash ; JPY ; 1\n  food ; JPY ; -1\n").unwrap();
+        let commodities: Vec<_> = tokens
+            .iter()
+            .filter_map(|t| match t.token() {
+                Token::Identifier(x) if x.as_ref() == "JPY" => Some(x.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(commodities.len(), 2);
+        assert!(std::sync::Arc::ptr_eq(&commodities[0], &commodities[1]));
+    }
+
+    proptest! {
+        #[test]
+        fn test_never_panics(content in any::<String>()) {
+            let _ = lex_string(&content);
+        }
+
+        #[test]
+        fn test_never_panics_on_journal_characters(content in "[-+*/0-9a-zA-Z;!%=(),.:\"\\ \t\n円€]{0,80}") {
+            let _ = lex_string(&content);
+        }
+    }
+
+    /// Every lexer, in the order in which they win ties.
+    const ALL_LEXERS: &[Lexer] = &[
+        basic::lex_whitespace,
+        basic::lex_comment,
+        basic::lex_string,
+        basic::lex_keyword,
+        identifier::lex,
+        timestamp::lex,
+        basic::lex_status,
+        basic::lex_description,
+        amount::lex,
+        amount::lex_percentage,
+        expression::lex_operator,
+        expression::lex_left_parenthesis,
+        expression::lex_right_parenthesis,
+        basic::lex_account_separator,
+        basic::lex_posting_separator,
+        basic::lex_equals,
+        basic::lex_line_separator,
+    ];
+
+    /// Lexes a single token by trying every lexer, which is what the dispatch must be equivalent to.
+    fn lex_single_token_exhaustively(input: StringScanner) -> Option<(usize, Token)> {
+        for mut lexer in [basic::lex_yaml_matter, basic::lex_indent] {
+            if let Ok((rest, t)) = lexer.parse(input.clone()) {
+                return Some((rest.location(), t));
+            }
+        }
+
+        let mut result: Option<(usize, Token)> = None;
+        for lexer in ALL_LEXERS {
+            if let Ok((rest, t)) = lexer(input.clone()) {
+                if result.as_ref().map_or(true, |r| rest.location() > r.0) {
+                    result = Some((rest.location(), t));
+                }
+            }
+        }
+        result
+    }
+
+    fn last_token_strategy() -> impl Strategy<Value = Option<Token>> {
+        prop::option::of(prop::sample::select(vec![
+            Token::LineSeparator,
+            Token::Indent,
+            Token::Whitespace,
+            Token::Timestamp(crate::tokens::Timestamp {
+                date: chrono::NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+                time: None,
+                offset: None,
+            }),
+            Token::Status(TransactionStatus::Cleared),
+            Token::Identifier("rent".into()),
+            Token::Amount(1000),
+            Token::RightParenthesis,
+            Token::Operator(crate::tokens::Operator::Times),
+            Token::PostingSeparator,
+        ]))
+    }
+
+    proptest! {
+        #[test]
+        fn test_dispatch_is_exhaustive(
+            content in "([ \t\n/;*!+=()\"%.,:-]|[0-9]{1,4}|2026-01-01|12:30|set|let|rent|---|円|€|é){0,12}",
+            last_token in last_token_strategy(),
+        ) {
+            let mut input = StringScanner::from(content.as_str());
+            if let Some(t) = last_token {
+                input.set_last_token(t);
+            }
+
+            let actual = lex_single_token(input.clone()).ok().map(|(rest, t)| (rest.location(), t));
+            prop_assert_eq!(actual, lex_single_token_exhaustively(input));
+        }
+    }
 }
@@ -1,7 +1,9 @@
-use super::core::{NomResult, StringScanner};
+use super::core::{error_at, NomResult, StringScanner};
+use super::error::LexerErrorDetails;
+use super::expression::follows_operand;
 use super::whitespace;
 use crate::tokens::Token;
-use nom::bytes::complete::tag;
+use nom::bytes::complete::{tag, take_while_m_n};
 use nom::character::complete::{digit1, one_of};
 use nom::combinator::opt;
 use nom::multi::many0;
@@ -9,11 +11,16 @@ use nom::sequence::preceded;
 use nom::Parser;
 
 pub fn lex(input: StringScanner) -> NomResult<Token> {
-    let original_input = input.clone();
+    let binary = follows_operand(&input);
 
     let (input, _) = opt(whitespace::whitespace).parse(input)?;
+    let start = input.location();
 
-    let (input, sign) = opt(one_of("+-")).parse(input)?;
+    // After an operand, a sign is a binary operator instead, e.g. `1000 - 300`.
+    let (input, sign) = match binary {
+        true => (input, None),
+        false => opt(one_of("+-")).parse(input)?,
+    };
     let (input, initial_digits) = digit1.parse(input)?;
     let (input, rest_digits) = many0(preceded(tag(","), digit1)).parse(input)?;
 
@@ -28,16 +35,40 @@ pub fn lex(input: StringScanner) -> NomResult<Token> {
             .join("")
     );
 
-    let amount: i64 = str::parse(&numstr).map_err(|_| {
-        nom::Err::Error(nom::error::make_error(
-            original_input,
-            nom::error::ErrorKind::IsNot,
-        ))
-    })?;
+    // Only digits are left, so the number can only fail to parse by being out of range.
+    let amount: i64 =
+        str::parse(&numstr).map_err(|_| error_at(start, LexerErrorDetails::AmountOutOfRange))?;
 
     Ok((input, Token::Amount(amount)))
 }
 
+/// Lexes a percentage with up to two decimal places, e.g. `10%` or `-2.5%`.
+pub fn lex_percentage(input: StringScanner) -> NomResult<Token> {
+    let (input, _) = opt(whitespace::whitespace).parse(input)?;
+    let start = input.location();
+
+    let (input, sign) = opt(one_of("+-")).parse(input)?;
+    let (input, integer) = digit1.parse(input)?;
+    let (input, fraction) = opt(preceded(
+        tag("."),
+        take_while_m_n(1, 2, |c: char| c.is_ascii_digit()),
+    ))
+    .parse(input)?;
+    let (input, _) = tag("%").parse(input)?;
+
+    let numstr = format!(
+        "{}{}{:0<2}",
+        sign.unwrap_or('+'),
+        integer.as_str(),
+        fraction.as_ref().map(|x| x.as_str()).unwrap_or("")
+    );
+
+    let basis_points: i64 =
+        str::parse(&numstr).map_err(|_| error_at(start, LexerErrorDetails::AmountOutOfRange))?;
+
+    Ok((input, Token::Percentage(basis_points)))
+}
+
 #[cfg(test)]
 mod test {
     #[rstest::rstest]
@@ -53,9 +84,68 @@ mod test {
         assert_eq!(x, result);
     }
 
+    #[test]
+    fn test_amount_after_operand_has_no_sign() {
+        let mut scanner: super::StringScanner = " - 300".into();
+        scanner.set_last_token(super::Token::Amount(1000));
+        super::lex(scanner).expect_err("Should have failed.");
+    }
+
+    #[rstest::rstest]
+    #[case::min("-9,223,372,036,854,775,808", i64::MIN)]
+    #[case::max("9223372036854775807", i64::MAX)]
+    fn test_amount_limits(#[case] input: &str, #[case] result: i64) {
+        let (_, output) = super::lex(input.into()).expect("Failed.");
+        assert_eq!(output, super::Token::Amount(result));
+    }
+
+    #[rstest::rstest]
+    #[case::too_large("9223372036854775808")]
+    #[case::too_small("-9,223,372,036,854,775,809")]
+    fn test_amount_out_of_range(#[case] input: &str) {
+        let Err(nom::Err::Failure(e)) = super::lex(input.into()) else {
+            panic!("Should have failed.");
+        };
+        assert!(matches!(
+            e.details,
+            super::LexerErrorDetails::AmountOutOfRange
+        ));
+    }
+
     #[rstest::rstest]
     #[case::non_numeric("asfasf")]
     fn test_amount_fails(#[case] input: &str) {
         super::lex(input.into()).expect_err("Failed.");
     }
+
+    #[rstest::rstest]
+    #[case::integer("10%", 1000)]
+    #[case::fraction(" 2.5%", 250)]
+    #[case::negative("-0.25%", -25)]
+    fn test_percentage_succeeds(#[case] input: &str, #[case] result: i64) {
+        let (_, output) = super::lex_percentage(input.into()).expect("Failed.");
+        assert_eq!(output, super::Token::Percentage(result));
+    }
+
+    #[rstest::rstest]
+    #[case::no_percent_sign("10")]
+    #[case::too_many_decimals("0.125%")]
+    fn test_percentage_fails(#[case] input: &str) {
+        super::lex_percentage(input.into()).expect_err("Should have failed.");
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_amount_is_exact_or_out_of_range(digits in "-?[0-9]{1,25}") {
+            let expected = digits.parse::<i128>().expect("Invalid test case.");
+            match super::lex(digits.as_str().into()) {
+                Ok((_, output)) => proptest::prop_assert_eq!(output, super::Token::Amount(expected as i64)),
+                Err(nom::Err::Failure(e)) => {
+                    proptest::prop_assert!(i64::try_from(expected).is_err());
+                    proptest::prop_assert!(matches!(e.details, super::LexerErrorDetails::AmountOutOfRange));
+                }
+                Err(e) => proptest::prop_assert!(false, "Unexpected error: {e:?}"),
+            }
+        }
+    }
 }
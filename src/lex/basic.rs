@@ -1,33 +1,29 @@
 use super::core::{error_at, StringScanner};
 use super::error::LexerErrorDetails;
-use crate::tokens::{Keyword, Token};
+use super::whitespace;
+use crate::tokens::{Keyword, Token, TransactionStatus};
 use nom::branch::alt;
-use nom::bytes::complete::{tag, take_until};
-use nom::character::complete::one_of;
-use nom::multi::many1;
-use nom::sequence::delimited;
+use nom::bytes::complete::{tag, take, take_until, take_while};
+use nom::character::complete::{none_of, one_of};
+use nom::combinator::{opt, recognize};
+use nom::multi::{many0, many1};
+use nom::sequence::{delimited, pair, preceded};
 use nom::Parser;
 
 use super::core::NomResult;
 
-fn internal_error<T>(input: &StringScanner, loc: usize, msg: &str) -> NomResult<T> {
-    Err(error_at(
-        input,
-        loc,
-        LexerErrorDetails::InternalError(msg.to_string()),
-    ))
+fn internal_error<T>(loc: usize, msg: &'static str) -> NomResult<T> {
+    Err(error_at(loc, LexerErrorDetails::InternalError(msg)))
 }
 
 pub fn lex_whitespace(input: StringScanner) -> NomResult<Token> {
     let t = input.get_last_token();
     match &t {
         None => internal_error(
-            &input,
             input.location(),
             "Whitespace only comes after a non-line separator token.",
         ),
         Some(Token::LineSeparator) => internal_error(
-            &input,
             input.location(),
             "Whitespace only comes after a non-line separator token.",
         ),
@@ -45,11 +41,8 @@ pub fn lex_indent(input: StringScanner) -> NomResult<Token> {
         Some(Token::LineSeparator) => {}
         Some(_) => {
             return Err(error_at(
-                &input,
                 input.location(),
-                LexerErrorDetails::InternalError(
-                    "Indent must be preceded by line separator.".to_string(),
-                ),
+                LexerErrorDetails::InternalError("Indent must be preceded by line separator."),
             ))
         }
     }
@@ -58,6 +51,64 @@ pub fn lex_indent(input: StringScanner) -> NomResult<Token> {
     Ok((input, Token::Indent))
 }
 
+/// Lexes the status marker of a transaction (`*` for cleared, `!` for pending),
+/// which may only come directly after the timestamp.
+pub fn lex_status(input: StringScanner) -> NomResult<Token> {
+    if !matches!(input.get_last_token(), Some(Token::Timestamp(_))) {
+        return internal_error(input.location(), "Status only comes after a timestamp.");
+    }
+
+    let (input, _) = opt(whitespace::whitespace).parse(input)?;
+    let (input, status) = one_of("*!").parse(input)?;
+    let status = match status {
+        '*' => TransactionStatus::Cleared,
+        _ => TransactionStatus::Pending,
+    };
+
+    Ok((input, Token::Status(status)))
+}
+
+/// Lexes the free-text description of a transaction, which comes after the timestamp and status
+/// and extends until the end of the line or the start of a comment, i.e. a `//` after whitespace.
+/// Trailing whitespace is not part of the description.
+pub fn lex_description(input: StringScanner) -> NomResult<Token> {
+    let after_timestamp = match input.get_last_token() {
+        Some(Token::Timestamp(_)) => true,
+        Some(Token::Status(_)) => false,
+        _ => {
+            return internal_error(
+                input.location(),
+                "Description only comes after a timestamp or status.",
+            )
+        }
+    };
+
+    let (input, space) = opt(whitespace::whitespace).parse(input)?;
+    // Text glued to a date (e.g. `2026-01-0112:30`) is a mistyped timestamp, not a description.
+    if after_timestamp && space.is_none() {
+        return internal_error(input.location(), "Expected whitespace.");
+    }
+    // A status marker right after the timestamp is never part of the description.
+    if after_timestamp && input.as_str().starts_with(['*', '!']) {
+        return internal_error(input.location(), "Expected status.");
+    }
+
+    // A comment only starts after whitespace, so that e.g. a URL can be part of the description.
+    let line = input.as_str().split('\n').next().unwrap_or_default();
+    let comment = line
+        .match_indices("//")
+        .map(|(i, _)| i)
+        .find(|&i| i == 0 || line[..i].ends_with([' ', '\t']))
+        .unwrap_or(line.len());
+    let text = line[..comment].trim_end();
+    if text.is_empty() {
+        return internal_error(input.location(), "Description cannot be empty.");
+    }
+
+    let (input, text) = take(text.chars().count()).parse(input)?;
+    Ok((input, Token::Description(text.as_str().into())))
+}
+
 pub fn lex_account_separator(input: StringScanner) -> NomResult<Token> {
     let (input, _) = tag("/").parse(input)?;
     Ok((input, Token::AccountSeparator))
@@ -68,10 +119,33 @@ pub fn lex_posting_separator(input: StringScanner) -> NomResult<Token> {
     Ok((input, Token::PostingSeparator))
 }
 
+pub fn lex_equals(input: StringScanner) -> NomResult<Token> {
+    let (input, _) = tag("=").parse(input)?;
+    Ok((input, Token::Equals))
+}
+
+/// Lexes a double-quoted string. Double quotes and backslashes inside the string
+/// must be escaped with a backslash, and the string may not span multiple lines.
+pub fn lex_string(input: StringScanner) -> NomResult<Token> {
+    let (input, chars) = delimited(
+        tag("\""),
+        many0(alt((
+            none_of("\"\\\n"),
+            preceded(tag("\\"), one_of("\"\\")),
+        ))),
+        tag("\""),
+    )
+    .parse(input)?;
+    Ok((
+        input,
+        Token::String(chars.into_iter().collect::<String>().into()),
+    ))
+}
+
 pub fn lex_comment(input: StringScanner) -> NomResult<Token> {
     let (input, _) = tag("//").parse(input)?;
     let (input, content) = take_until("\n").parse(input)?;
-    Ok((input, Token::Comment(content.as_str().to_string())))
+    Ok((input, Token::Comment(content.as_str().into())))
 }
 
 pub fn lex_line_separator(input: StringScanner) -> NomResult<Token> {
@@ -80,10 +154,33 @@ pub fn lex_line_separator(input: StringScanner) -> NomResult<Token> {
 }
 
 pub fn lex_keyword(input: StringScanner) -> NomResult<Token> {
-    let (input, kw) = alt([tag("set")]).parse(input)?;
+    // Keywords start a directive, so anywhere else (e.g. in an account name) they are just identifiers.
+    if !matches!(input.get_last_token(), None | Some(Token::LineSeparator)) {
+        return internal_error(
+            input.location(),
+            "Keywords only come at the start of a line.",
+        );
+    }
+
+    let (input, kw) = alt([
+        tag("set"),
+        tag("auto"),
+        tag("budget"),
+        tag("account"),
+        tag("with"),
+        tag("end"),
+        tag("let"),
+    ])
+    .parse(input)?;
 
     let the_kw = match kw.as_str() {
         "set" => Keyword::Set,
+        "auto" => Keyword::Auto,
+        "budget" => Keyword::Budget,
+        "account" => Keyword::Account,
+        "with" => Keyword::With,
+        "end" => Keyword::End,
+        "let" => Keyword::Let,
         _ => {
             panic!("Unhandled keyword. This is a bug.");
         }
@@ -93,17 +190,34 @@ pub fn lex_keyword(input: StringScanner) -> NomResult<Token> {
 }
 
 pub fn lex_yaml_matter(input: StringScanner) -> NomResult<Token> {
-    let start = "  ---\n  ";
-    let end = "\n  ---";
-    let (input, yamlstr) = delimited(tag(start), take_until(end), tag(end)).parse(input)?;
-    let stripped = yamlstr.as_str().replace("\n  ", "\n");
-    let Ok(parsed) = serde_yaml::from_str(&stripped) else {
+    // Like an indent, YAML matter always starts a line.
+    if !matches!(input.get_last_token(), None | Some(Token::LineSeparator)) {
+        return internal_error(
+            input.location(),
+            "YAML matter must be preceded by line separator.",
+        );
+    }
+
+    // YAML matter can be indented by any amount of spaces (at least 2),
+    // as long as all lines (including the delimiters) use the same indentation.
+    let (_, indent) = recognize(pair(tag("  "), take_while(|c| c == ' '))).parse(input.clone())?;
+    let indent = indent.as_str().to_string();
+    let start = format!("{indent}---\n{indent}");
+    let end = format!("\n{indent}---");
+    let (input, yamlstr) = delimited(
+        tag(start.as_str()),
+        take_until(end.as_str()),
+        tag(end.as_str()),
+    )
+    .parse(input)?;
+    let stripped = yamlstr.as_str().replace(&format!("\n{indent}"), "\n");
+    let Ok(parsed) = serde_yaml::from_str::<serde_yaml::Mapping>(&stripped) else {
         return Err(nom::Err::Error(nom::error::make_error(
             input,
             nom::error::ErrorKind::IsNot,
         )));
     };
-    Ok((input, Token::YamlMatter(parsed)))
+    Ok((input, Token::YamlMatter(parsed.into())))
 }
 
 #[cfg(test)]
@@ -127,4 +241,124 @@ mod test {
         assert!(matches!(t, Token::Comment(_)));
         assert_eq!(rest.as_str(), "\n");
     }
+
+    #[rstest::rstest]
+    #[case::simple(r#""+09:00""#, "+09:00")]
+    #[case::empty(r#""""#, "")]
+    #[case::escapes(r#""a \"b\" \\ c""#, r#"a "b" \ c"#)]
+    fn test_string(#[case] input: &str, #[case] expected: &str) {
+        let (rest, t) = lex_string(input.into()).expect("Failed.");
+        assert_eq!(t, Token::String(expected.into()));
+        assert!(rest.is_empty());
+    }
+
+    #[rstest::rstest]
+    #[case::unterminated(r#""foo"#)]
+    #[case::newline("\"foo\nbar\"")]
+    #[case::unknown_escape(r#""\n""#)]
+    fn test_string_fails(#[case] input: &str) {
+        lex_string(input.into()).expect_err("Should have failed.");
+    }
+
+    #[rstest::rstest]
+    #[case::cleared(" * Coffee", TransactionStatus::Cleared, " Coffee")]
+    #[case::pending("!", TransactionStatus::Pending, "")]
+    fn test_status(
+        #[case] input: &str,
+        #[case] status: TransactionStatus,
+        #[case] remaining: &str,
+    ) {
+        let mut scanner: StringScanner = input.into();
+        scanner.set_last_token(Token::Timestamp(
+            chrono::DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+                .unwrap()
+                .into(),
+        ));
+        let (rest, t) = lex_status(scanner).expect("Failed.");
+        assert_eq!(t, Token::Status(status));
+        assert_eq!(rest.as_str(), remaining);
+    }
+
+    #[test]
+    fn test_status_requires_timestamp() {
+        let mut scanner: StringScanner = "*".into();
+        scanner.set_last_token(Token::Indent);
+        lex_status(scanner).expect_err("Should have failed.");
+    }
+
+    #[rstest::rstest]
+    #[case::simple(" Coffee shop", "Coffee shop", "")]
+    #[case::until_newline(" Coffee shop  \n", "Coffee shop", "  \n")]
+    #[case::until_comment(" Coffee shop // latte", "Coffee shop", " // latte")]
+    #[case::url(
+        " Payment to https://example.com // paid",
+        "Payment to https://example.com",
+        " // paid"
+    )]
+    #[case::comment_after_tab(" Coffee shop\t// latte", "Coffee shop", "\t// latte")]
+    #[case::multibyte(" 喫茶店 ☕", "喫茶店 ☕", "")]
+    fn test_description(#[case] input: &str, #[case] expected: &str, #[case] remaining: &str) {
+        let mut scanner: StringScanner = input.into();
+        scanner.set_last_token(Token::Status(TransactionStatus::Cleared));
+        let (rest, t) = lex_description(scanner).expect("Failed.");
+        assert_eq!(t, Token::Description(expected.into()));
+        assert_eq!(rest.as_str(), remaining);
+    }
+
+    #[rstest::rstest]
+    #[case::empty("   \n")]
+    #[case::only_comment(" // comment")]
+    fn test_description_fails(#[case] input: &str) {
+        let mut scanner: StringScanner = input.into();
+        scanner.set_last_token(Token::Status(TransactionStatus::Cleared));
+        lex_description(scanner).expect_err("Should have failed.");
+    }
+
+    #[rstest::rstest]
+    #[case::spaced(" 12:30", true)]
+    #[case::glued_to_timestamp("12:30", false)]
+    fn test_description_after_timestamp(#[case] input: &str, #[case] ok: bool) {
+        let mut scanner: StringScanner = input.into();
+        scanner.set_last_token(Token::Timestamp(crate::tokens::Timestamp {
+            date: chrono::NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+            time: None,
+            offset: None,
+        }));
+        assert_eq!(lex_description(scanner).is_ok(), ok);
+    }
+
+    #[rstest::rstest]
+    #[case::two_spaces("  ", "")]
+    #[case::only_two_spaces("    ", "  ")]
+    #[case::tab_is_not_part_of_indent("  \t", "\t")]
+    fn test_indent(#[case] input: &str, #[case] remaining: &str) {
+        let (rest, t) = lex_indent(input.into()).expect("Failed.");
+        assert!(matches!(t, Token::Indent));
+        assert_eq!(rest.as_str(), remaining);
+    }
+
+    #[rstest::rstest]
+    #[case::two_spaces("  ---\n  foo: bar\n  baz:\n    - 1\n  ---")]
+    #[case::four_spaces("    ---\n    foo: bar\n    baz:\n      - 1\n    ---")]
+    fn test_yaml_matter(#[case] input: &str) {
+        let (rest, t) = lex_yaml_matter(input.into()).expect("Failed.");
+        let Token::YamlMatter(mapping) = t else {
+            panic!("Should have been YAML matter.");
+        };
+        assert_eq!(mapping.get("foo").expect("Failed."), "bar");
+        assert_eq!(mapping.get("baz").expect("Failed.")[0], 1);
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn test_yaml_matter_with_inconsistent_indent() {
+        lex_yaml_matter("    ---\n  foo: bar\n    ---".into()).expect_err("Should have failed.");
+    }
+
+    #[test]
+    fn test_yaml_matter_only_starts_a_line() {
+        let mut scanner: StringScanner = "  ---\n  foo: bar\n  ---".into();
+        scanner.set_last_token(Token::Operator(crate::tokens::Operator::Times));
+        lex_yaml_matter(scanner).expect_err("Should have failed.");
+    }
 }
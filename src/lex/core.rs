@@ -6,7 +6,8 @@ pub struct StringScanner {
     pub content: std::sync::Arc<str>,
     offset: usize,
     limit: usize,
-    last_token: Option<Token>,
+    /// Shared, since the scanner is cloned all the time while lexing.
+    last_token: Option<std::rc::Rc<Token>>,
 }
 
 impl StringScanner {
@@ -30,11 +31,11 @@ impl StringScanner {
     }
 
     pub fn set_last_token(&mut self, token: Token) {
-        self.last_token = Some(token);
+        self.last_token = Some(std::rc::Rc::new(token));
     }
 
     pub fn get_last_token(&self) -> Option<&Token> {
-        self.last_token.as_ref()
+        self.last_token.as_deref()
     }
 }
 
@@ -71,63 +72,44 @@ impl nom::Offset for StringScanner {
     }
 }
 
-struct StringScannerIteratorBase {
-    chars: Vec<(usize, char)>,
-    index: usize,
+/// Iterates over the characters of a scanner (along with their byte offsets), decoding them lazily
+/// so that lexers which only look at a few characters do not pay for the rest of the input.
+pub struct StringScannerIdxIterator {
+    content: std::sync::Arc<str>,
+    start: usize,
+    position: usize,
+    limit: usize,
 }
 
-impl From<&StringScanner> for StringScannerIteratorBase {
+impl From<&StringScanner> for StringScannerIdxIterator {
     fn from(value: &StringScanner) -> Self {
         Self {
-            chars: value.content[value.offset..value.limit]
-                .chars()
-                .enumerate()
-                .collect(),
-            index: 0,
+            content: value.content.clone(),
+            start: value.offset,
+            position: value.offset,
+            limit: value.limit,
         }
     }
 }
 
-impl std::iter::Iterator for StringScannerIteratorBase {
-    type Item = usize;
+impl std::iter::Iterator for StringScannerIdxIterator {
+    type Item = (usize, char);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index >= self.chars.len() {
-            return None;
-        }
-        self.index += 1;
-        Some(self.index - 1)
+        let c = self.content[self.position..self.limit].chars().next()?;
+        let index = self.position - self.start;
+        self.position += c.len_utf8();
+        Some((index, c))
     }
 }
 
-pub struct StringScannerIterator(StringScannerIteratorBase);
+pub struct StringScannerIterator(StringScannerIdxIterator);
 
 impl std::iter::Iterator for StringScannerIterator {
     type Item = char;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next().map(|i| self.0.chars[i].1)
-    }
-}
-
-impl From<StringScannerIteratorBase> for StringScannerIterator {
-    fn from(value: StringScannerIteratorBase) -> Self {
-        Self(value)
-    }
-}
-
-pub struct StringScannerIdxIterator(StringScannerIteratorBase);
-
-impl std::iter::Iterator for StringScannerIdxIterator {
-    type Item = (usize, char);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.0.next().map(|i| self.0.chars[i])
-    }
-}
-impl From<StringScannerIteratorBase> for StringScannerIdxIterator {
-    fn from(value: StringScannerIteratorBase) -> Self {
-        Self(value)
+        self.0.next().map(|(_, c)| c)
     }
 }
 
@@ -197,60 +179,56 @@ impl nom::Input for StringScanner {
     }
 
     fn iter_elements(&self) -> Self::Iter {
-        StringScannerIterator::from(StringScannerIteratorBase::from(self))
+        StringScannerIterator(StringScannerIdxIterator::from(self))
     }
 
     fn iter_indices(&self) -> Self::IterIndices {
-        StringScannerIdxIterator::from(StringScannerIteratorBase::from(self))
+        StringScannerIdxIterator::from(self)
     }
 
     fn slice_index(&self, count: usize) -> Result<usize, nom::Needed> {
-        let total = self.as_str().char_indices().count();
-        let idx = match self.as_str().char_indices().nth(count) {
-            Some((x, _)) => x,
-            None if count == total => self.as_str().len(),
-            _ => {
-                return Err(nom::Needed::new(count - total));
+        // Only looks at the first `count` characters, since the input is usually the rest of the file.
+        let s = self.as_str();
+        let mut total = 0;
+        for (i, _) in s.char_indices() {
+            if total == count {
+                return Ok(i);
             }
-        };
+            total += 1;
+        }
 
-        Ok(idx)
+        match count == total {
+            true => Ok(s.len()),
+            false => Err(nom::Needed::new(count - total)),
+        }
     }
 }
 
-impl nom::error::ParseError<StringScanner> for LexerError {
+/// The error of a single lexer. Most of them only mean that the lexer did not apply, so unlike
+/// [LexerError] they are kept small and cheap to create.
+#[derive(Debug)]
+pub struct ScanError {
+    pub location: usize,
+    pub details: LexerErrorDetails,
+}
+
+impl nom::error::ParseError<StringScanner> for ScanError {
     fn from_error_kind(input: StringScanner, kind: nom::error::ErrorKind) -> Self {
         Self {
-            content: input.content.clone(),
             location: input.offset,
-            details: LexerErrorDetails::InternalError(format!("{:#?}", kind)),
-            previous_tokens: vec![],
+            details: LexerErrorDetails::NomError(kind),
         }
     }
 
     fn append(input: StringScanner, kind: nom::error::ErrorKind, _: Self) -> Self {
-        Self {
-            content: input.content.clone(),
-            location: input.offset,
-            details: LexerErrorDetails::InternalError(format!("{:#?}", kind)),
-            previous_tokens: vec![],
-        }
+        Self::from_error_kind(input, kind)
     }
 }
 
-pub type NomResult<T> = nom::IResult<StringScanner, T, LexerError>;
+pub type NomResult<T> = nom::IResult<StringScanner, T, ScanError>;
 
 pub type LexerResult<T> = Result<T, LexerError>;
 
-pub(crate) fn error_at(
-    scanner: &StringScanner,
-    location: usize,
-    details: LexerErrorDetails,
-) -> nom::Err<LexerError> {
-    nom::Err::Failure(LexerError {
-        content: scanner.content.clone(),
-        location,
-        details,
-        previous_tokens: vec![],
-    })
+pub(crate) fn error_at(location: usize, details: LexerErrorDetails) -> nom::Err<ScanError> {
+    nom::Err::Failure(ScanError { location, details })
 }
@@ -1,9 +1,17 @@
 #[derive(Clone, Debug)]
 pub enum LexerErrorDetails {
     NothingMatched,
-    InternalError(String),
+    /// A number which does not fit in an amount (a 64-bit integer).
+    AmountOutOfRange,
+    /// A lexer did not apply. These errors are frequent, so they must be cheap to create.
+    InternalError(&'static str),
+    /// A nom parser inside a lexer did not apply.
+    NomError(nom::error::ErrorKind),
 }
 
+/// The number of tokens before an error which are shown along with it.
+pub(super) const PREVIOUS_TOKENS: usize = 5;
+
 /// The main error type for the parser stage.
 #[derive(Debug)]
 pub struct LexerError {
@@ -13,6 +21,7 @@ pub struct LexerError {
     pub location: usize,
     /// Details about the exact error.
     pub details: LexerErrorDetails,
+    /// The last few tokens before the error (one more than are shown, to tell whether there were more).
     pub previous_tokens: Vec<super::DecoratedToken>,
 }
 
@@ -20,15 +29,18 @@ impl std::fmt::Display for LexerError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Lexer Error: ")?;
         let msg = match &self.details {
-            LexerErrorDetails::InternalError(_) => "Internal error.",
+            LexerErrorDetails::InternalError(_) | LexerErrorDetails::NomError(_) => {
+                "Internal error."
+            }
             LexerErrorDetails::NothingMatched => "Encountered invalid characters.",
+            LexerErrorDetails::AmountOutOfRange => "Amount is out of range.",
         };
         writeln!(f, "{msg} Previous Tokens: [")?;
-        for token in self.previous_tokens.iter().rev().take(5) {
+        for token in self.previous_tokens.iter().rev().take(PREVIOUS_TOKENS) {
             // TODO: pretty print the tokens as well.
             writeln!(f, "  {}", token.token())?;
         }
-        if self.previous_tokens.len() > 5 {
+        if self.previous_tokens.len() > PREVIOUS_TOKENS {
             writeln!(f, "  ...")?;
         }
         writeln!(f, "]")?;
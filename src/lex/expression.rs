@@ -0,0 +1,108 @@
+//! Operators and parentheses of amount expressions, e.g. `(1200 * 3) + 450`.
+use super::core::{NomResult, StringScanner};
+use super::whitespace;
+use crate::tokens::{Operator, Token};
+use nom::bytes::complete::tag;
+use nom::character::complete::one_of;
+use nom::combinator::{not, opt, peek};
+use nom::sequence::{preceded, terminated};
+use nom::Parser;
+
+/// Whether the last token ends an operand (including a variable), in which case a following sign is a binary operator.
+pub fn follows_operand(input: &StringScanner) -> bool {
+    matches!(
+        input.get_last_token(),
+        Some(Token::Amount(_) | Token::RightParenthesis | Token::Identifier(_))
+    )
+}
+
+pub fn lex_operator(input: StringScanner) -> NomResult<Token> {
+    let binary = follows_operand(&input);
+    let after_identifier = matches!(input.get_last_token(), Some(Token::Identifier(_)));
+
+    let (input, space) = opt(whitespace::whitespace).parse(input)?;
+    let (input, c) = if binary && after_identifier && space.is_none() {
+        // Without a space, a `/` after an identifier separates the segments of an account.
+        terminated(one_of("+-*"), not(tag("/"))).parse(input)?
+    } else if binary {
+        // `//` starts a comment, not a division.
+        terminated(one_of("+-*/"), not(tag("/"))).parse(input)?
+    } else {
+        // A negative number is a single amount, so a unary minus only comes before parentheses.
+        terminated(
+            one_of("-"),
+            peek(preceded(opt(whitespace::whitespace), tag("("))),
+        )
+        .parse(input)?
+    };
+
+    let operator = match c {
+        '+' => Operator::Plus,
+        '-' => Operator::Minus,
+        '*' => Operator::Times,
+        _ => Operator::Divide,
+    };
+
+    Ok((input, Token::Operator(operator)))
+}
+
+pub fn lex_left_parenthesis(input: StringScanner) -> NomResult<Token> {
+    let (input, _) = preceded(opt(whitespace::whitespace), tag("(")).parse(input)?;
+    Ok((input, Token::LeftParenthesis))
+}
+
+pub fn lex_right_parenthesis(input: StringScanner) -> NomResult<Token> {
+    let (input, _) = preceded(opt(whitespace::whitespace), tag(")")).parse(input)?;
+    Ok((input, Token::RightParenthesis))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[rstest::rstest]
+    #[case::plus(" + 450", Operator::Plus)]
+    #[case::minus("-300", Operator::Minus)]
+    #[case::times(" * 3", Operator::Times)]
+    #[case::divide("/ 3", Operator::Divide)]
+    fn test_binary_operator(#[case] input: &str, #[case] expected: Operator) {
+        let mut scanner: StringScanner = input.into();
+        scanner.set_last_token(Token::Amount(1000));
+        let (_, t) = lex_operator(scanner).expect("Failed.");
+        assert_eq!(t, Token::Operator(expected));
+    }
+
+    #[test]
+    fn test_unary_minus() {
+        let mut scanner: StringScanner = " - (1000)".into();
+        scanner.set_last_token(Token::PostingSeparator);
+        let (rest, t) = lex_operator(scanner).expect("Failed.");
+        assert_eq!(t, Token::Operator(Operator::Minus));
+        assert_eq!(rest.as_str(), " (1000)");
+    }
+
+    #[test]
+    fn test_comment_is_not_a_division() {
+        let mut scanner: StringScanner = " // comment".into();
+        scanner.set_last_token(Token::Amount(1000));
+        lex_operator(scanner).expect_err("Should have failed.");
+    }
+
+    #[rstest::rstest]
+    #[case::spaced(" / 3", true)]
+    #[case::account_separator("/cash", false)]
+    fn test_divide_after_identifier(#[case] input: &str, #[case] ok: bool) {
+        let mut scanner: StringScanner = input.into();
+        scanner.set_last_token(Token::Identifier("rent".into()));
+        assert_eq!(lex_operator(scanner).is_ok(), ok);
+    }
+
+    #[rstest::rstest]
+    #[case::not_before_parenthesis(" -1000")]
+    #[case::divide("/ 3")]
+    fn test_operator_requires_operand(#[case] input: &str) {
+        let mut scanner: StringScanner = input.into();
+        scanner.set_last_token(Token::PostingSeparator);
+        lex_operator(scanner).expect_err("Should have failed.");
+    }
+}
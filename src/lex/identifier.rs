@@ -1,23 +1,60 @@
 use super::core::{NomResult, StringScanner};
-use crate::tokens::Token;
-use nom::bytes::complete::is_a;
-use nom::combinator::{opt, recognize};
+use crate::tokens::{Text, Token};
+use nom::bytes::complete::{take_while, take_while1};
+use nom::combinator::recognize;
 use nom::sequence::pair;
 use nom::Parser;
 
-const ALPHA: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJQKLMNOPQRSTUVWXYZ";
-const NUMBERS: &str = "0123456789";
 const NON_FIRST_SYMBOLS: &str = "-";
 const OTHER_SYMBOLS: &str = "_:";
 
+/// Currency symbols are allowed anywhere in an identifier, so that they can be used as commodities (e.g. `€`).
+fn is_currency_symbol(c: char) -> bool {
+    matches!(
+        c,
+        '$' | '\u{a2}'..='\u{a5}' | '\u{20a0}'..='\u{20cf}' | '\u{ffe0}' | '\u{ffe1}' | '\u{ffe5}' | '\u{ffe6}'
+    )
+}
+
+fn is_first(c: char) -> bool {
+    unicode_ident::is_xid_start(c) || OTHER_SYMBOLS.contains(c) || is_currency_symbol(c)
+}
+
+fn is_rest(c: char) -> bool {
+    unicode_ident::is_xid_continue(c)
+        || NON_FIRST_SYMBOLS.contains(c)
+        || OTHER_SYMBOLS.contains(c)
+        || is_currency_symbol(c)
+}
+
+/// Returns whether `s` would be lexed as a single identifier.
+pub fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(is_first) && chars.all(is_rest)
+}
+
 pub fn lex(input: StringScanner) -> NomResult<Token> {
-    let first: String = String::new() + ALPHA + OTHER_SYMBOLS;
-    let rest: String = String::new() + ALPHA + NUMBERS + NON_FIRST_SYMBOLS + OTHER_SYMBOLS;
+    let (input, x) = recognize(pair(take_while1(is_first), take_while(is_rest))).parse(input)?;
+
+    Ok((input, Token::Identifier(x.as_str().into())))
+}
 
-    let (input, x) =
-        recognize(pair(is_a(first.as_str()), opt(is_a(rest.as_str())))).parse(input)?;
+/// Makes equal identifiers share the same text, since a journal repeats the same few account and
+/// commodity names over and over.
+#[derive(Default)]
+pub struct Interner(std::collections::HashSet<Text>);
 
-    Ok((input, Token::Identifier(x.as_str().to_string())))
+impl Interner {
+    pub fn intern(&mut self, token: Token) -> Token {
+        let Token::Identifier(x) = token else {
+            return token;
+        };
+        if let Some(y) = self.0.get(&x) {
+            return Token::Identifier(y.clone());
+        }
+        self.0.insert(x.clone());
+        Token::Identifier(x)
+    }
 }
 
 #[cfg(test)]
@@ -27,18 +64,27 @@ mod test {
     #[case::alpha_alphanum("f123")]
     #[case::alpha_symbols("foo:bar-baz")]
     #[case::underscore_prefix("_f123")]
+    #[case::all_ascii_letters("ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz")]
+    #[case::japanese("資産")]
+    #[case::mixed_scripts("現金-JPY")]
+    #[case::currency_symbol("€")]
+    #[case::fullwidth_currency_symbol("￥")]
     fn test_identifier_succeeds(#[case] input: &str) {
         let (_, output) = super::lex(input.into()).expect("Failed.");
         let super::Token::Identifier(x) = output else {
             panic!("Should have been an identifier.");
         };
-        assert_eq!(x, input);
+        assert_eq!(x.as_ref(), input);
+        assert!(super::is_identifier(input));
     }
 
     #[rstest::rstest]
     #[case::numeric_prefix("1asfasf")]
     #[case::dash_prefix("-1asfasf")]
+    #[case::fullwidth_digit_prefix("１月")]
+    #[case::emoji("🎉")]
     fn test_identifier_fails(#[case] input: &str) {
         super::lex(input.into()).expect_err("Failed.");
+        assert!(!super::is_identifier(input));
     }
 }
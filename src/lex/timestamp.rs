@@ -1,87 +1,184 @@
 use super::core::{NomResult, StringScanner};
+use super::whitespace;
 use crate::tokens::{Timestamp, Token};
 
-use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
-use nom::bytes::complete::take;
+use chrono::{FixedOffset, NaiveDate, NaiveTime};
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take_while_m_n};
+use nom::character::complete::one_of;
+use nom::combinator::{map, opt};
+use nom::sequence::preceded;
 use nom::Parser;
 
-fn default_offset() -> FixedOffset {
-    FixedOffset::east_opt(0).unwrap()
+fn digits(
+    min: usize,
+    max: usize,
+) -> impl Parser<StringScanner, Output = StringScanner, Error = super::core::ScanError> {
+    take_while_m_n(min, max, |c: char| c.is_ascii_digit())
 }
 
-fn lex_datetime(input: StringScanner) -> NomResult<Timestamp> {
-    let (input, dt_str) = take(29usize).parse(input)?;
+fn number(s: &StringScanner) -> u32 {
+    // The digits have already been validated by the lexer, so this can only fail on overflow,
+    // which is impossible for the lengths we accept.
+    s.as_str().parse().expect("This should never happen.")
+}
 
-    let dt = match DateTime::parse_from_str(dt_str.as_str(), "%Y-%m-%d %H:%M:%S%.3f%:z") {
-        Ok(x) => x,
-        Err(_) => {
-            return Err(nom::Err::Error(nom::error::make_error(
-                dt_str,
-                nom::error::ErrorKind::IsNot,
-            )))
-        }
-    };
+fn invalid<T>(input: StringScanner) -> NomResult<T> {
+    Err(nom::Err::Error(nom::error::make_error(
+        input,
+        nom::error::ErrorKind::IsNot,
+    )))
+}
 
-    Ok((input, dt))
+/// Used once the input is known to be a timestamp, so that e.g. `2026-01-01 24:00` is rejected
+/// instead of being lexed as a date followed by other tokens.
+fn failure<T>(input: StringScanner) -> NomResult<T> {
+    Err(nom::Err::Failure(nom::error::make_error(
+        input,
+        nom::error::ErrorKind::IsNot,
+    )))
 }
 
 fn lex_date(input: StringScanner) -> NomResult<NaiveDate> {
-    let (input, datestr_part) = take(10usize).parse(input)?;
-
-    let datestr = datestr_part.as_str().trim();
-    if datestr.len() != 10 {
-        return Err(nom::Err::Error(nom::error::make_error(
-            datestr_part,
-            nom::error::ErrorKind::IsNot,
-        )));
+    let original_input = input.clone();
+    let (input, (y, _, m, _, d)) =
+        (digits(4, 4), tag("-"), digits(2, 2), tag("-"), digits(2, 2)).parse(input)?;
+
+    match NaiveDate::from_ymd_opt(number(&y) as i32, number(&m), number(&d)) {
+        Some(date) => Ok((input, date)),
+        None => invalid(original_input),
     }
+}
 
-    let date = match NaiveDate::parse_from_str(datestr, "%Y-%m-%d") {
-        Ok(x) => x,
-        Err(_) => {
-            return Err(nom::Err::Error(nom::error::make_error(
-                datestr_part,
-                nom::error::ErrorKind::IsNot,
-            )))
+/// Lexes a time of the form `HH:MM`, `HH:MM:SS` or `HH:MM:SS.mmm`.
+/// The fractional part may have up to 3 digits, i.e. timestamps have millisecond precision.
+fn lex_time(input: StringScanner) -> NomResult<NaiveTime> {
+    let original_input = input.clone();
+    let (input, (h, _, m, rest)) = (
+        digits(2, 2),
+        tag(":"),
+        digits(2, 2),
+        opt(preceded(
+            tag(":"),
+            (digits(2, 2), opt(preceded(tag("."), digits(1, 9)))),
+        )),
+    )
+        .parse(input)?;
+
+    let (s, milli) = match rest {
+        None => (0, 0),
+        Some((s, None)) => (number(&s), 0),
+        Some((_, Some(frac))) if frac.as_str().len() > 3 => return failure(original_input),
+        Some((s, Some(frac))) => {
+            let padded = format!("{:0<3}", frac.as_str());
+            (
+                number(&s),
+                padded.parse().expect("This should never happen."),
+            )
         }
     };
 
-    Ok((input, date))
+    match NaiveTime::from_hms_milli_opt(number(&h), number(&m), s, milli) {
+        Some(time) => Ok((input, time)),
+        None => failure(original_input),
+    }
+}
+
+fn lex_offset(input: StringScanner) -> NomResult<FixedOffset> {
+    let original_input = input.clone();
+    let (input, seconds) = alt((
+        map(tag("Z"), |_| 0),
+        map(
+            (one_of("+-"), digits(2, 2), tag(":"), digits(2, 2)),
+            |(sign, h, _, m)| {
+                let seconds = (number(&h) * 3600 + number(&m) * 60) as i32;
+                if sign == '-' {
+                    -seconds
+                } else {
+                    seconds
+                }
+            },
+        ),
+    ))
+    .parse(input)?;
+
+    match FixedOffset::east_opt(seconds) {
+        Some(offset) => Ok((input, offset)),
+        None => failure(original_input),
+    }
 }
 
+/// Lexes a timestamp. Only the date is mandatory, e.g. all of the following are valid:
+///
+/// - `2026-01-01`
+/// - `2026-01-01 12:30`
+/// - `2026-01-01 12:30:45.123`
+/// - `2026-01-01 12:30+09:00`
+/// - `2026-01-01 12:30:45Z`
+///
+/// An offset may only be specified together with a time.
 pub fn lex(input: StringScanner) -> NomResult<Token> {
-    let (input, dt) = match lex_datetime(input.clone()) {
-        Ok(x) => x,
-        Err(_) => {
-            let (input, date) = lex_date(input)?;
-            let ndt = NaiveDateTime::new(date, NaiveTime::from_hms_opt(0, 0, 0).unwrap());
-            let dt = DateTime::from_naive_utc_and_offset(ndt, default_offset());
-
-            (input, dt)
-        }
+    // Like amounts, timestamps absorb any preceding whitespace. Otherwise the year of a posting date
+    // (e.g. ` 2026-01-05`) would be a longer match as an amount than the date is as a timestamp.
+    let (input, _) = opt(whitespace::whitespace).parse(input)?;
+    let (input, date) = lex_date(input)?;
+    let (input, rest) = opt(preceded(
+        whitespace::whitespace,
+        (lex_time, opt(lex_offset)),
+    ))
+    .parse(input)?;
+
+    let (time, offset) = match rest {
+        Some((time, offset)) => (Some(time), offset),
+        None => (None, None),
     };
 
-    Ok((input, Token::Timestamp(dt)))
+    Ok((input, Token::Timestamp(Timestamp { date, time, offset })))
 }
 
 #[cfg(test)]
 mod test {
-    use super::{lex, Token};
-    use chrono::DateTime;
+    use super::{lex, Timestamp, Token};
+    use chrono::{FixedOffset, NaiveDate, NaiveTime};
 
     #[rstest::rstest]
-    #[case::date("2026-01-01", "2026-01-01 00:00:00.000Z")]
-    #[case::timestamp_with_timezone("2026-01-01 00:00:00.000+00:00", "2026-01-01 00:00:00.000Z")]
-    fn test_succeeds(#[case] input: &str, #[case] timestamp: &str) {
-        let (_, result) = lex(input.into()).expect("Failed.");
+    #[case::date("2026-01-01", None, None)]
+    #[case::hours_minutes("2026-01-01 12:30", Some((12, 30, 0, 0)), None)]
+    #[case::seconds("2026-01-01 12:30:45", Some((12, 30, 45, 0)), None)]
+    #[case::milliseconds("2026-01-01 12:30:45.123", Some((12, 30, 45, 123)), None)]
+    #[case::short_fraction("2026-01-01 12:30:45.5", Some((12, 30, 45, 500)), None)]
+    #[case::offset("2026-01-01 00:00:00.000+09:00", Some((0, 0, 0, 0)), Some(9 * 3600))]
+    #[case::negative_offset("2026-01-01 12:30-05:30", Some((12, 30, 0, 0)), Some(-19800))]
+    #[case::zulu("2026-01-01 12:30Z", Some((12, 30, 0, 0)), Some(0))]
+    fn test_succeeds(
+        #[case] input: &str,
+        #[case] time: Option<(u32, u32, u32, u32)>,
+        #[case] offset: Option<i32>,
+    ) {
+        let (rest, result) = lex(input.into()).expect("Failed.");
+        assert!(rest.is_empty());
         assert_eq!(
             result,
-            Token::Timestamp(DateTime::parse_from_rfc3339(timestamp).expect("Invalid test case."))
+            Token::Timestamp(Timestamp {
+                date: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+                time: time.map(|(h, m, s, ms)| NaiveTime::from_hms_milli_opt(h, m, s, ms).unwrap()),
+                offset: offset.map(|x| FixedOffset::east_opt(x).unwrap()),
+            })
         )
     }
 
+    #[test]
+    fn test_offset_requires_time() {
+        let (rest, _) = lex("2026-01-01+09:00".into()).expect("Failed.");
+        assert_eq!(rest.as_str(), "+09:00");
+    }
+
     #[rstest::rstest]
     #[case::preceding_newline("\n2026-01-01")]
+    #[case::invalid_date("2026-02-30")]
+    #[case::short_year("226-01-01")]
+    #[case::invalid_time("2026-01-01 24:00")]
+    #[case::too_many_fraction_digits("2026-01-01 12:30:45.1234")]
     fn test_fails(#[case] input: &str) {
         lex(input.into()).expect_err("Should have failed.");
     }
@@ -1,15 +1,47 @@
-#[derive(Clone, serde::Deserialize, Debug, PartialEq, serde::Serialize)]
+/// The version of the exported JSON format.
+///
+/// This must be incremented whenever the output types change in a way that is not backwards-compatible
+/// for consumers (e.g. removing or renaming a field, or changing its type). Since the types deny unknown
+/// fields, adding a field is not backwards-compatible either.
+///
+/// - 2: Timestamps include milliseconds and the UTC offset, and the header has `default_timezone`.
+/// - 3: Postings have `attributes` and `date`.
+/// - 4: Transactions have `status` and `description`.
+/// - 5: The header has `conversion_postings`, and transactions have `conversion`.
+/// - 6: Postings have `generated`.
+/// - 7: The journal has `budgets`.
+/// - 8: The journal has `accounts`.
+/// - 9: The header has `title` and `fiscal_year_start`.
+/// - 10: The header no longer has `title` and `fiscal_year_start`.
+/// - 11: The header has `title` and `fiscal_year_start` again.
+pub const SCHEMA_VERSION: u32 = 11;
+
+#[derive(Clone, serde::Deserialize, Debug, PartialEq, serde::Serialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct JournalHeader {
+    /// A human-readable name for the journal, if one was set.
+    pub title: Option<String>,
     pub default_commodity: String,
+    /// The timezone applied to timestamps without an explicit offset, if one was set.
+    pub default_timezone: Option<String>,
+    /// Whether implicit conversions are balanced with postings to `equity/conversion`.
+    pub conversion_postings: bool,
+    /// The month (1-12) in which the fiscal year starts, if one was set.
+    pub fiscal_year_start: Option<u32>,
 }
 
-#[derive(Clone, serde::Deserialize, Debug, PartialEq, serde::Serialize)]
+#[derive(Clone, serde::Deserialize, Debug, PartialEq, serde::Serialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct Posting {
     pub account: String,
     pub commodity: String,
     pub amount: i64,
+    #[schemars(with = "serde_json::Map<String, serde_json::Value>")]
+    pub attributes: serde_yaml::Mapping,
+    /// The date on which the posting took effect, if it differs from the transaction's.
+    pub date: Option<chrono::NaiveDate>,
+    /// Whether the posting was added by boki (e.g. by an auto rule) rather than written in the journal.
+    pub generated: bool,
 }
 
 impl Default for Posting {
@@ -18,40 +50,142 @@ impl Default for Posting {
             account: "".to_string(),
             commodity: "".to_string(),
             amount: 0,
+            attributes: serde_yaml::Mapping::default(),
+            date: None,
+            generated: false,
         }
     }
 }
 
 pub type TransactionTimestamp = chrono::DateTime<chrono::FixedOffset>;
 
-#[derive(Clone, serde::Deserialize, Debug, PartialEq, serde::Serialize)]
+/// Timestamps are always exported in the same form, e.g. `2026-01-01T00:00:00.000+09:00`.
+mod timestamp_format {
+    use super::TransactionTimestamp;
+
+    const FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3f%:z";
+
+    pub fn serialize<S: serde::Serializer>(
+        ts: &TransactionTimestamp,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&ts.format(FORMAT))
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<TransactionTimestamp, D::Error> {
+        let s: String = serde::Deserialize::deserialize(deserializer)?;
+        TransactionTimestamp::parse_from_rfc3339(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(
+    Clone, Copy, serde::Deserialize, Debug, PartialEq, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionStatus {
+    Pending,
+    Cleared,
+}
+
+#[derive(Clone, serde::Deserialize, Debug, PartialEq, serde::Serialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct TransactionHeader {
+    #[serde(with = "timestamp_format")]
+    #[schemars(with = "TransactionTimestamp")]
     pub timestamp: TransactionTimestamp,
+    pub status: Option<TransactionStatus>,
+    /// A free-text description of the transaction, e.g. the payee.
+    pub description: Option<String>,
+    #[schemars(with = "serde_json::Map<String, serde_json::Value>")]
     pub attributes: serde_yaml::Mapping,
 }
 
-#[derive(Clone, serde::Deserialize, Debug, PartialEq, serde::Serialize)]
+/// An exchange between two commodities, inferred from a transaction in which
+/// exactly two commodities are unbalanced.
+#[derive(Clone, serde::Deserialize, Debug, PartialEq, serde::Serialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Conversion {
+    pub from_commodity: String,
+    pub from_amount: i64,
+    pub to_commodity: String,
+    pub to_amount: i64,
+    /// How many units of `to_commodity` one unit of `from_commodity` is worth.
+    pub rate: f64,
+}
+
+#[derive(Clone, serde::Deserialize, Debug, PartialEq, serde::Serialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct Transaction {
     pub header: TransactionHeader,
     pub postings: Vec<Posting>,
+    pub conversion: Option<Conversion>,
+}
+
+#[derive(
+    Clone, Copy, serde::Deserialize, Debug, PartialEq, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Monthly,
+    Quarterly,
+    Yearly,
 }
 
-#[derive(Clone, serde::Deserialize, Debug, PartialEq, serde::Serialize)]
+#[derive(
+    Clone, Copy, serde::Deserialize, Debug, PartialEq, Eq, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountType {
+    Assets,
+    Liabilities,
+    Equity,
+    Income,
+    Expense,
+}
+
+/// The amount which is planned to be posted to an account (and its subaccounts) in each period.
+#[derive(Clone, serde::Deserialize, Debug, PartialEq, serde::Serialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Budget {
+    pub period: BudgetPeriod,
+    pub account: String,
+    pub commodity: String,
+    pub amount: i64,
+}
+
+#[derive(Clone, serde::Deserialize, Debug, PartialEq, serde::Serialize, schemars::JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct Journal {
+    /// The version of the format this journal was exported in.
+    pub schema_version: u32,
     pub header: JournalHeader,
     pub transactions: Vec<Transaction>,
+    pub budgets: Vec<Budget>,
+    /// The declared account types. Each applies to the account and all of its subaccounts.
+    pub accounts: std::collections::BTreeMap<String, AccountType>,
 }
 
 impl Default for Journal {
     fn default() -> Self {
         Journal {
+            schema_version: SCHEMA_VERSION,
             header: JournalHeader {
+                title: None,
                 default_commodity: "".to_string(),
+                default_timezone: None,
+                conversion_postings: false,
+                fiscal_year_start: None,
             },
             transactions: vec![],
+            budgets: vec![],
+            accounts: Default::default(),
         }
     }
 }
+
+/// Returns a JSON Schema document describing [Journal].
+pub fn json_schema() -> schemars::Schema {
+    schemars::schema_for!(Journal)
+}
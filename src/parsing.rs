@@ -2,6 +2,7 @@ mod basic;
 mod combinators;
 mod core;
 mod error;
+mod expression;
 
 pub use core::{get_next, peek_next, Parser, ParserResult, TokenScanner};
 pub use error::{ParserError, ParserErrorDetails};
@@ -9,7 +10,9 @@ pub use error::{ParserError, ParserErrorDetails};
 pub use combinators::{many, one_of, optional, preceded, take_until, terminated};
 
 pub use basic::{
-    parse_account_separator, parse_amount, parse_comment, parse_identifier, parse_indent,
-    parse_keyword, parse_line_separator, parse_posting_separator, parse_timestamp,
-    parse_whitespace, parse_yaml_matter,
+    parse_account_separator, parse_amount, parse_comment, parse_description, parse_equals,
+    parse_identifier, parse_indent, parse_keyword, parse_left_parenthesis, parse_line_separator,
+    parse_operator, parse_percentage, parse_posting_separator, parse_right_parenthesis,
+    parse_status, parse_string, parse_timestamp, parse_whitespace, parse_yaml_matter,
 };
+pub use expression::parse_expression;
@@ -14,7 +14,7 @@ macro_rules! parse_token {
                     return Err(error::ParserError {
                         location: i,
                         details: error::ParserErrorDetails::ExpectedSomethingElse(
-                            $tname.to_string(),
+                            $tname,
                             t.clone(),
                         ),
                     })
@@ -31,6 +31,20 @@ parse_token!(
     tokens::Token::Timestamp(ts),
     *ts
 );
+parse_token!(
+    parse_status,
+    tokens::TransactionStatus,
+    tokens::TOKEN_NAME_STATUS,
+    tokens::Token::Status(x),
+    *x
+);
+parse_token!(
+    parse_description,
+    String,
+    tokens::TOKEN_NAME_DESCRIPTION,
+    tokens::Token::Description(x),
+    x.to_string()
+);
 parse_token!(
     parse_amount,
     i64,
@@ -38,12 +52,47 @@ parse_token!(
     tokens::Token::Amount(x),
     *x
 );
+parse_token!(
+    parse_percentage,
+    i64,
+    tokens::TOKEN_NAME_PERCENTAGE,
+    tokens::Token::Percentage(x),
+    *x
+);
+parse_token!(
+    parse_operator,
+    tokens::Operator,
+    tokens::TOKEN_NAME_OPERATOR,
+    tokens::Token::Operator(x),
+    *x
+);
+parse_token!(
+    parse_left_parenthesis,
+    (),
+    tokens::TOKEN_NAME_LEFT_PARENTHESIS,
+    tokens::Token::LeftParenthesis,
+    ()
+);
+parse_token!(
+    parse_right_parenthesis,
+    (),
+    tokens::TOKEN_NAME_RIGHT_PARENTHESIS,
+    tokens::Token::RightParenthesis,
+    ()
+);
 parse_token!(
     parse_identifier,
     String,
     tokens::TOKEN_NAME_IDENTIFIER,
     tokens::Token::Identifier(x),
-    x.clone()
+    x.to_string()
+);
+parse_token!(
+    parse_string,
+    String,
+    tokens::TOKEN_NAME_STRING,
+    tokens::Token::String(x),
+    x.to_string()
 );
 parse_token!(
     parse_account_separator,
@@ -59,6 +108,13 @@ parse_token!(
     tokens::Token::PostingSeparator,
     ()
 );
+parse_token!(
+    parse_equals,
+    (),
+    tokens::TOKEN_NAME_EQUALS,
+    tokens::Token::Equals,
+    ()
+);
 parse_token!(
     parse_line_separator,
     (),
@@ -71,7 +127,7 @@ parse_token!(
     serde_yaml::Mapping,
     tokens::TOKEN_NAME_YAML_MATTER,
     tokens::Token::YamlMatter(x),
-    x.clone()
+    x.as_ref().clone()
 );
 parse_token!(
     parse_indent,
@@ -103,7 +159,7 @@ pub fn parse_keyword(scanner: &mut TokenScanner, kw: tokens::Keyword) -> ParserR
         _ => Err(error::ParserError {
             location: i,
             details: error::ParserErrorDetails::ExpectedSomethingElse(
-                tokens::TOKEN_NAME_KEYWORD.to_string(),
+                tokens::TOKEN_NAME_KEYWORD,
                 t.clone(),
             ),
         }),
@@ -224,14 +224,16 @@ mod test {
 
     #[test]
     fn test_one_of_first() {
-        let mut scanner = TokenScanner::from_slice(&[tokens::Token::Indent]);
+        let tokens = [tokens::Token::Indent];
+        let mut scanner = TokenScanner::from_slice(&tokens);
         let parsers = [parse_succeed, parse_fail];
         one_of(&parsers).parse(&mut scanner).expect("Failed.");
     }
 
     #[test]
     fn test_one_of_second() {
-        let mut scanner = TokenScanner::from_slice(&[tokens::Token::Indent]);
+        let tokens = [tokens::Token::Indent];
+        let mut scanner = TokenScanner::from_slice(&tokens);
         let parsers = [parse_fail, parse_succeed];
         one_of(&parsers).parse(&mut scanner).expect("Failed.");
     }
@@ -3,23 +3,24 @@ use crate::tokens;
 
 pub type ParserResult<T> = Result<T, error::ParserError>;
 
-pub struct TokenScanner {
-    tokens: Vec<tokens::Token>,
+/// Scans over borrowed tokens, so that parsing (and backtracking) never copies them.
+pub struct TokenScanner<'a> {
+    tokens: &'a [tokens::Token],
     location: usize,
     offset: usize,
 }
 
-impl TokenScanner {
-    pub fn from_slice(slice: &[tokens::Token]) -> Self {
+impl<'a> TokenScanner<'a> {
+    pub fn from_slice(slice: &'a [tokens::Token]) -> Self {
         TokenScanner {
-            tokens: slice.to_vec(),
+            tokens: slice,
             offset: 0,
             location: 0,
         }
     }
 }
 
-impl TokenScanner {
+impl<'a> TokenScanner<'a> {
     pub fn tell(&self) -> usize {
         self.offset + self.location
     }
@@ -41,24 +42,24 @@ impl TokenScanner {
     pub fn advance(&mut self, i: usize) -> ParserResult<()> {
         self.seek(self.location + i)
     }
-    pub fn peek(&self) -> Option<&tokens::Token> {
+    pub fn peek(&self) -> Option<&'a tokens::Token> {
         self.tokens.get(self.location)
     }
 
     // TODO:
     #[allow(clippy::should_implement_trait)]
-    pub fn next(&mut self) -> Option<&tokens::Token> {
+    pub fn next(&mut self) -> Option<&'a tokens::Token> {
         let t = self.tokens.get(self.location);
         self.location += 1;
         t
     }
 
-    pub fn tokens(&self) -> &[tokens::Token] {
+    pub fn tokens(&self) -> &'a [tokens::Token] {
         &self.tokens[self.offset..]
     }
 }
 
-pub fn peek_next(scanner: &TokenScanner) -> ParserResult<&tokens::Token> {
+pub fn peek_next<'a>(scanner: &TokenScanner<'a>) -> ParserResult<&'a tokens::Token> {
     match scanner.peek() {
         None => Err(error::ParserError {
             location: scanner.tell(),
@@ -68,7 +69,7 @@ pub fn peek_next(scanner: &TokenScanner) -> ParserResult<&tokens::Token> {
     }
 }
 
-pub fn get_next(scanner: &mut TokenScanner) -> ParserResult<&tokens::Token> {
+pub fn get_next<'a>(scanner: &mut TokenScanner<'a>) -> ParserResult<&'a tokens::Token> {
     let location = scanner.tell();
     match scanner.next() {
         None => Err(error::ParserError {
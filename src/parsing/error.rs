@@ -4,7 +4,7 @@ use crate::tokens;
 pub enum ParserErrorDetails {
     BranchingError(String, Vec<ParserError>),
     Nested(String, Box<ParserError>),
-    ExpectedSomethingElse(String, tokens::Token),
+    ExpectedSomethingElse(&'static str, tokens::Token),
     IllegalImplementation(String),
     /// We consumed all tokens without seeing an EOF token.
     Incomplete,
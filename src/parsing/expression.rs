@@ -0,0 +1,245 @@
+//! Amount expressions, with the usual precedence: `*` and `/` bind tighter than `+` and `-`,
+//! and operators of the same precedence are evaluated left to right.
+use crate::ast::{Expression, Operator, Rounding};
+use crate::parsing::basic::{
+    parse_amount, parse_identifier, parse_left_parenthesis, parse_operator, parse_right_parenthesis,
+};
+use crate::parsing::combinators::{many, one_of, preceded, terminated};
+use crate::parsing::core::{Parser, ParserResult, TokenScanner};
+use crate::parsing::error;
+use crate::tokens;
+
+/// Parses one of the given operators.
+fn parse_operator_of(
+    operators: &'static [Operator],
+) -> impl Fn(&mut TokenScanner) -> ParserResult<Operator> {
+    move |scanner| {
+        let i = scanner.tell();
+        let operator = parse_operator(scanner)?;
+        if !operators.contains(&operator) {
+            return Err(error::ParserError {
+                location: i,
+                details: error::ParserErrorDetails::ExpectedSomethingElse(
+                    tokens::TOKEN_NAME_OPERATOR,
+                    tokens::Token::Operator(operator),
+                ),
+            });
+        }
+
+        Ok(operator)
+    }
+}
+
+fn parse_literal(scanner: &mut TokenScanner) -> ParserResult<Expression> {
+    parse_amount(scanner).map(Expression::Amount)
+}
+
+fn parse_variable(scanner: &mut TokenScanner) -> ParserResult<Expression> {
+    parse_identifier(scanner).map(Expression::Variable)
+}
+
+fn parse_parenthesized(scanner: &mut TokenScanner) -> ParserResult<Expression> {
+    let x = preceded(
+        parse_left_parenthesis,
+        terminated(parse_expression, parse_right_parenthesis),
+    )
+    .parse(scanner)?;
+    Ok(Expression::Parenthesized(Box::new(x)))
+}
+
+/// Parses a rounding function, e.g. `round(rent / 3)`.
+fn parse_rounded(scanner: &mut TokenScanner) -> ParserResult<Expression> {
+    let i = scanner.tell();
+    let name = parse_identifier(scanner)?;
+    let Some(rounding) = Rounding::from_name(&name) else {
+        return Err(error::ParserError {
+            location: i,
+            details: error::ParserErrorDetails::Other(format!(
+                "Expected round, floor or ceil but found: {name}"
+            )),
+        });
+    };
+
+    let x = preceded(
+        parse_left_parenthesis,
+        terminated(parse_expression, parse_right_parenthesis),
+    )
+    .parse(scanner)?;
+    Ok(Expression::Rounded(rounding, Box::new(x)))
+}
+
+fn parse_negation(scanner: &mut TokenScanner) -> ParserResult<Expression> {
+    let x = preceded(parse_operator_of(&[Operator::Minus]), parse_factor).parse(scanner)?;
+    Ok(Expression::Negation(Box::new(x)))
+}
+
+fn parse_factor(scanner: &mut TokenScanner) -> ParserResult<Expression> {
+    one_of(&[
+        parse_literal,
+        parse_rounded,
+        parse_variable,
+        parse_parenthesized,
+        parse_negation,
+    ])
+    .parse(scanner)
+}
+
+/// Parses a left-associative chain of `operand`s joined by any of `operators`.
+fn parse_chain(
+    scanner: &mut TokenScanner,
+    operators: &'static [Operator],
+    operand: fn(&mut TokenScanner) -> ParserResult<Expression>,
+) -> ParserResult<Expression> {
+    let first = operand(scanner)?;
+    let rest = many(|scanner: &mut TokenScanner| {
+        let operator = parse_operator_of(operators)(scanner)?;
+        Ok((operator, operand(scanner)?))
+    })
+    .parse(scanner)?;
+
+    Ok(rest.into_iter().fold(first, |l, (operator, r)| {
+        Expression::Binary(Box::new(l), operator, Box::new(r))
+    }))
+}
+
+fn parse_term(scanner: &mut TokenScanner) -> ParserResult<Expression> {
+    parse_chain(scanner, &[Operator::Times, Operator::Divide], parse_factor)
+}
+
+pub fn parse_expression(scanner: &mut TokenScanner) -> ParserResult<Expression> {
+    parse_chain(scanner, &[Operator::Plus, Operator::Minus], parse_term)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tokens::Token;
+
+    fn binary(l: Expression, operator: Operator, r: Expression) -> Expression {
+        Expression::Binary(Box::new(l), operator, Box::new(r))
+    }
+
+    #[test]
+    fn test_literal() {
+        let tokens = [Token::Amount(1000)];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        assert_eq!(
+            parse_expression(&mut scanner).expect("Failed."),
+            1000.into()
+        );
+    }
+
+    #[test]
+    fn test_precedence() {
+        let tokens = [
+            Token::Amount(450),
+            Token::Operator(Operator::Plus),
+            Token::Amount(1200),
+            Token::Operator(Operator::Times),
+            Token::Amount(3),
+            Token::Operator(Operator::Minus),
+            Token::Amount(1),
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        let expected = binary(
+            binary(
+                450.into(),
+                Operator::Plus,
+                binary(1200.into(), Operator::Times, 3.into()),
+            ),
+            Operator::Minus,
+            1.into(),
+        );
+        assert_eq!(parse_expression(&mut scanner).expect("Failed."), expected);
+    }
+
+    #[test]
+    fn test_parentheses_and_negation() {
+        let tokens = [
+            Token::Operator(Operator::Minus),
+            Token::LeftParenthesis,
+            Token::Amount(1000),
+            Token::Operator(Operator::Divide),
+            Token::Amount(3),
+            Token::RightParenthesis,
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        let expression = parse_expression(&mut scanner).expect("Failed.");
+        assert_eq!(expression.to_string(), "-(1000 / 3)");
+    }
+
+    #[test]
+    fn test_variable() {
+        let tokens = [
+            Token::Identifier("rent".into()),
+            Token::Operator(Operator::Divide),
+            Token::Amount(3),
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        let expected = binary(
+            Expression::Variable("rent".to_string()),
+            Operator::Divide,
+            3.into(),
+        );
+        assert_eq!(parse_expression(&mut scanner).expect("Failed."), expected);
+    }
+
+    #[test]
+    fn test_rounded() {
+        let tokens = [
+            Token::Identifier("floor".into()),
+            Token::LeftParenthesis,
+            Token::Identifier("rent".into()),
+            Token::Operator(Operator::Divide),
+            Token::Amount(3),
+            Token::RightParenthesis,
+            Token::Operator(Operator::Times),
+            Token::Amount(2),
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        let expected = binary(
+            Expression::Rounded(
+                Rounding::Floor,
+                Box::new(binary(
+                    Expression::Variable("rent".to_string()),
+                    Operator::Divide,
+                    3.into(),
+                )),
+            ),
+            Operator::Times,
+            2.into(),
+        );
+        assert_eq!(parse_expression(&mut scanner).expect("Failed."), expected);
+    }
+
+    #[test]
+    fn test_rounding_name_is_a_variable_without_parentheses() {
+        let tokens = [Token::Identifier("round".into())];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        assert_eq!(
+            parse_expression(&mut scanner).expect("Failed."),
+            Expression::Variable("round".to_string())
+        );
+    }
+
+    #[test]
+    fn test_stops_before_other_tokens() {
+        let tokens = [
+            Token::Amount(1000),
+            Token::Operator(Operator::Times),
+            Token::Amount(3),
+            Token::PostingSeparator,
+        ];
+        let mut scanner = TokenScanner::from_slice(&tokens);
+        parse_expression(&mut scanner).expect("Failed.");
+        assert_eq!(scanner.peek(), Some(&Token::PostingSeparator));
+    }
+
+    #[rstest::rstest]
+    #[case::unclosed(&[Token::LeftParenthesis, Token::Amount(1000)])]
+    #[case::no_operand(&[Token::Operator(Operator::Times), Token::Amount(3)])]
+    fn test_invalid(#[case] tokens: &[Token]) {
+        let mut scanner = TokenScanner::from_slice(tokens);
+        parse_expression(&mut scanner).expect_err("Should have failed.");
+    }
+}
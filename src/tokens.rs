@@ -1,31 +1,165 @@
-use chrono::{DateTime, FixedOffset};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 
-pub type Timestamp = DateTime<FixedOffset>;
+/// A timestamp as it was written in the journal.
+///
+/// Only the date is mandatory. If the time or the offset are omitted, it is up to the compiler
+/// to decide how to interpret the timestamp (e.g. by using the journal's default timezone).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub struct Timestamp {
+    pub date: NaiveDate,
+    pub time: Option<NaiveTime>,
+    #[serde(with = "offset_seconds")]
+    pub offset: Option<FixedOffset>,
+}
+
+/// chrono cannot (de)serialize a [FixedOffset] by itself, so it is stored as seconds east of UTC.
+mod offset_seconds {
+    use chrono::FixedOffset;
+
+    pub fn serialize<S: serde::Serializer>(
+        offset: &Option<FixedOffset>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&offset.map(|x| x.local_minus_utc()), serializer)
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<FixedOffset>, D::Error> {
+        let seconds: Option<i32> = serde::Deserialize::deserialize(deserializer)?;
+        seconds
+            .map(|x| {
+                FixedOffset::east_opt(x)
+                    .ok_or_else(|| serde::de::Error::custom("The offset is out of range."))
+            })
+            .transpose()
+    }
+}
+
+impl Timestamp {
+    /// The date and time as written, without taking the offset into account.
+    /// A missing time is considered to be midnight.
+    pub fn naive_local(&self) -> NaiveDateTime {
+        NaiveDateTime::new(self.date, self.time.unwrap_or(NaiveTime::MIN))
+    }
+}
+
+impl From<DateTime<FixedOffset>> for Timestamp {
+    fn from(value: DateTime<FixedOffset>) -> Self {
+        Self {
+            date: value.date_naive(),
+            time: Some(value.time()),
+            offset: Some(*value.offset()),
+        }
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    /// Writes the timestamp in the same form that the lexer accepts, omitting anything that is not needed.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.date.format("%Y-%m-%d"))?;
+
+        let Some(time) = self.time else {
+            return Ok(());
+        };
+
+        write!(f, " {}", time.format("%H:%M"))?;
+        if time.second() != 0 || time.nanosecond() != 0 {
+            write!(f, "{}", time.format(":%S%.f"))?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, "{offset}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum TransactionStatus {
+    Pending,
+    Cleared,
+}
+
+impl std::fmt::Display for TransactionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            TransactionStatus::Pending => write!(f, "!"),
+            TransactionStatus::Cleared => write!(f, "*"),
+        }
+    }
+}
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Keyword {
     Set,
+    Auto,
+    Budget,
+    Account,
+    With,
+    End,
+    Let,
 }
 
 impl std::fmt::Display for Keyword {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self {
             Keyword::Set => write!(f, "set"),
+            Keyword::Auto => write!(f, "auto"),
+            Keyword::Budget => write!(f, "budget"),
+            Keyword::Account => write!(f, "account"),
+            Keyword::With => write!(f, "with"),
+            Keyword::End => write!(f, "end"),
+            Keyword::Let => write!(f, "let"),
+        }
+    }
+}
+
+/// An arithmetic operator in an amount expression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Operator {
+    Plus,
+    Minus,
+    Times,
+    Divide,
+}
+
+impl std::fmt::Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            Operator::Plus => write!(f, "+"),
+            Operator::Minus => write!(f, "-"),
+            Operator::Times => write!(f, "*"),
+            Operator::Divide => write!(f, "/"),
         }
     }
 }
 
+/// The text of a token. It is shared, so that tokens are cheap to clone and equal identifiers can
+/// point to the same allocation.
+pub type Text = std::sync::Arc<str>;
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Token {
     Keyword(Keyword),
     Timestamp(Timestamp),
+    Status(TransactionStatus),
+    Description(Text),
     Amount(i64),
-    Identifier(String),
+    /// A percentage in basis points, e.g. `12.5%` is 1250.
+    Percentage(i64),
+    Operator(Operator),
+    LeftParenthesis,
+    RightParenthesis,
+    Identifier(Text),
+    String(Text),
     AccountSeparator,
     PostingSeparator,
+    /// The `=` of a `let` definition.
+    Equals,
     LineSeparator,
-    Comment(String),
-    YamlMatter(serde_yaml::Mapping),
+    Comment(Text),
+    YamlMatter(std::sync::Arc<serde_yaml::Mapping>),
     Whitespace,
     Indent,
     Eof,
@@ -33,10 +167,18 @@ pub enum Token {
 
 pub const TOKEN_NAME_KEYWORD: &str = "keyword";
 pub const TOKEN_NAME_TIMESTAMP: &str = "timestamp";
+pub const TOKEN_NAME_STATUS: &str = "status";
+pub const TOKEN_NAME_DESCRIPTION: &str = "description";
 pub const TOKEN_NAME_AMOUNT: &str = "amount";
+pub const TOKEN_NAME_PERCENTAGE: &str = "percentage";
+pub const TOKEN_NAME_OPERATOR: &str = "operator";
+pub const TOKEN_NAME_LEFT_PARENTHESIS: &str = "left parenthesis";
+pub const TOKEN_NAME_RIGHT_PARENTHESIS: &str = "right parenthesis";
 pub const TOKEN_NAME_IDENTIFIER: &str = "identifier";
+pub const TOKEN_NAME_STRING: &str = "string";
 pub const TOKEN_NAME_ACCOUNT_SEPARATOR: &str = "account separator";
 pub const TOKEN_NAME_POSTING_SEPARATOR: &str = "posting separator";
+pub const TOKEN_NAME_EQUALS: &str = "equals sign";
 pub const TOKEN_NAME_LINE_SEPARATOR: &str = "line separator";
 pub const TOKEN_NAME_COMMENT: &str = "comment";
 pub const TOKEN_NAME_YAML_MATTER: &str = "YAML matter";
@@ -53,10 +195,18 @@ impl Token {
         match self {
             Token::Keyword(_) => TOKEN_NAME_KEYWORD,
             Token::Timestamp(_) => TOKEN_NAME_TIMESTAMP,
+            Token::Status(_) => TOKEN_NAME_STATUS,
+            Token::Description(_) => TOKEN_NAME_DESCRIPTION,
             Token::Amount(_) => TOKEN_NAME_AMOUNT,
+            Token::Percentage(_) => TOKEN_NAME_PERCENTAGE,
+            Token::Operator(_) => TOKEN_NAME_OPERATOR,
+            Token::LeftParenthesis => TOKEN_NAME_LEFT_PARENTHESIS,
+            Token::RightParenthesis => TOKEN_NAME_RIGHT_PARENTHESIS,
             Token::Identifier(_) => TOKEN_NAME_IDENTIFIER,
+            Token::String(_) => TOKEN_NAME_STRING,
             Token::AccountSeparator => TOKEN_NAME_ACCOUNT_SEPARATOR,
             Token::PostingSeparator => TOKEN_NAME_POSTING_SEPARATOR,
+            Token::Equals => TOKEN_NAME_EQUALS,
             Token::LineSeparator => TOKEN_NAME_LINE_SEPARATOR,
             Token::Comment(_) => TOKEN_NAME_COMMENT,
             Token::YamlMatter(_) => TOKEN_NAME_YAML_MATTER,
@@ -72,10 +222,18 @@ impl std::fmt::Display for Token {
         match &self {
             Token::Keyword(kw) => write!(f, "Keyword({kw})"),
             Token::Timestamp(ts) => write!(f, "Timestamp({ts})"),
+            Token::Status(x) => write!(f, "Status({x})"),
+            Token::Description(x) => write!(f, "Description({x})"),
             Token::Amount(x) => write!(f, "Amount({x})"),
+            Token::Percentage(x) => write!(f, "Percentage({x})"),
+            Token::Operator(x) => write!(f, "Operator({x})"),
+            Token::LeftParenthesis => write!(f, "LeftParenthesis"),
+            Token::RightParenthesis => write!(f, "RightParenthesis"),
             Token::Identifier(x) => write!(f, "Identifier({x})"),
+            Token::String(x) => write!(f, "String({x:?})"),
             Token::AccountSeparator => write!(f, "AccountSeparator"),
             Token::PostingSeparator => write!(f, "PostingSeparator"),
+            Token::Equals => write!(f, "Equals"),
             Token::LineSeparator => write!(f, "LineSeparator"),
             Token::Comment(x) => write!(f, "Comment({x})"),
             Token::YamlMatter(_) => write!(f, "Yaml Matter"),
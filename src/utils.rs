@@ -1,9 +1,51 @@
 //! Anything that isn't worth getting a dedicated module goes here.
 
+/// Returns the number of columns `s` takes up in a terminal, e.g. 2 for each CJK character.
+pub fn display_width(s: &str) -> usize {
+    unicode_width::UnicodeWidthStr::width(s)
+}
+
 pub fn indent_string(s: &str) -> String {
     format!("  {}", s.replace("\n", "\n  "))
 }
 
+/// Returns whether `account` is `parent` itself or one of its subaccounts.
+pub fn is_same_or_subaccount(parent: &str, account: &str) -> bool {
+    account
+        .strip_prefix(parent)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Divides `numerator` by a non-zero `denominator`, rounding half away from zero (e.g. 5 / 2 is 3 and -5 / 2 is -3).
+pub fn divide_rounded(numerator: i128, denominator: i128) -> i128 {
+    let quotient = numerator / denominator;
+    if (numerator % denominator).abs() * 2 >= denominator.abs() {
+        quotient + numerator.signum() * denominator.signum()
+    } else {
+        quotient
+    }
+}
+
+/// Divides `numerator` by a non-zero `denominator`, rounding towards negative infinity (e.g. -5 / 2 is -3).
+pub fn divide_floor(numerator: i128, denominator: i128) -> i128 {
+    let quotient = numerator / denominator;
+    if numerator % denominator != 0 && (numerator < 0) != (denominator < 0) {
+        quotient - 1
+    } else {
+        quotient
+    }
+}
+
+/// Divides `numerator` by a non-zero `denominator`, rounding towards positive infinity (e.g. 5 / 2 is 3).
+pub fn divide_ceil(numerator: i128, denominator: i128) -> i128 {
+    let quotient = numerator / denominator;
+    if numerator % denominator != 0 && (numerator < 0) == (denominator < 0) {
+        quotient + 1
+    } else {
+        quotient
+    }
+}
+
 pub fn get_position_in_content(content: &str, location: usize) -> (usize, usize) {
     let mut i = 0;
     let mut line_number = 0;
@@ -24,11 +66,12 @@ pub fn get_position_in_content(content: &str, location: usize) -> (usize, usize)
     (line_number, character_position)
 }
 
-fn arrow_to(character_position: usize) -> Vec<String> {
+/// Draws an arrow pointing at the given (display) column.
+fn arrow_to(column: usize) -> Vec<String> {
     let mut lines = vec![];
-    lines.push(format!("          {}Λ", " ".repeat(character_position + 1)));
-    lines.push(format!("          {}│", " ".repeat(character_position + 1)));
-    lines.push(format!("  here ───{}┘", "─".repeat(character_position + 1)));
+    lines.push(format!("          {}Λ", " ".repeat(column + 1)));
+    lines.push(format!("          {}│", " ".repeat(column + 1)));
+    lines.push(format!("  here ───{}┘", "─".repeat(column + 1)));
 
     lines
 }
@@ -50,7 +93,8 @@ pub fn pretty_print_file_error(content: &str, location: usize, message: &str) ->
     for (i, line) in all_lines[min_line..max_line].iter().enumerate() {
         if i == line_number - min_line {
             the_lines.push(format!("{: >10}|{line}", i + min_line + 1));
-            the_lines.extend(arrow_to(character_position));
+            let prefix: String = line.chars().take(character_position).collect();
+            the_lines.extend(arrow_to(display_width(&prefix)));
         } else {
             the_lines.push(format!("{: >10}|{line}", i + min_line + 1));
         }
@@ -78,7 +122,8 @@ pub fn pretty_print_location(content: &str, location: usize) -> String {
     for (i, line) in all_lines[min_line..max_line].iter().enumerate() {
         if i == line_number - min_line {
             the_lines.push(format!("{: >10}|{line}", i + min_line + 1));
-            the_lines.extend(arrow_to(character_position));
+            let prefix: String = line.chars().take(character_position).collect();
+            the_lines.extend(arrow_to(display_width(&prefix)));
         } else {
             the_lines.push(format!("{: >10}|{line}", i + min_line + 1));
         }
@@ -87,3 +132,67 @@ pub fn pretty_print_location(content: &str, location: usize) -> String {
 
     the_lines.join("\n")
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[rstest::rstest]
+    #[case::same("expense", "expense", true)]
+    #[case::subaccount("expense", "expense/food", true)]
+    #[case::prefix_only("expense", "expenses", false)]
+    #[case::parent("expense/food", "expense", false)]
+    fn test_is_same_or_subaccount(
+        #[case] parent: &str,
+        #[case] account: &str,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(is_same_or_subaccount(parent, account), expected);
+    }
+
+    #[rstest::rstest]
+    #[case::exact(1000, 10, 100)]
+    #[case::round_down(1000, 3, 333)]
+    #[case::round_half_up(5, 2, 3)]
+    #[case::round_half_away_from_zero(-5, 2, -3)]
+    #[case::negative_denominator(2000, -3, -667)]
+    fn test_divide_rounded(
+        #[case] numerator: i128,
+        #[case] denominator: i128,
+        #[case] expected: i128,
+    ) {
+        assert_eq!(divide_rounded(numerator, denominator), expected);
+    }
+
+    #[rstest::rstest]
+    #[case::exact(1000, 10, 100, 100)]
+    #[case::positive(5, 2, 2, 3)]
+    #[case::negative(-5, 2, -3, -2)]
+    #[case::negative_denominator(5, -2, -3, -2)]
+    #[case::both_negative(-5, -2, 2, 3)]
+    fn test_divide_floor_and_ceil(
+        #[case] numerator: i128,
+        #[case] denominator: i128,
+        #[case] floor: i128,
+        #[case] ceil: i128,
+    ) {
+        assert_eq!(divide_floor(numerator, denominator), floor);
+        assert_eq!(divide_ceil(numerator, denominator), ceil);
+    }
+
+    #[test]
+    fn test_pretty_print_location_points_at_wide_characters() {
+        let content = "資産 x";
+        let location = content.find('x').unwrap();
+        let lines: Vec<String> = pretty_print_location(content, location)
+            .lines()
+            .map(|x| x.to_string())
+            .collect();
+
+        let column = |line: &str, c: char| {
+            let prefix: String = line.chars().take_while(|x| *x != c).collect();
+            display_width(&prefix)
+        };
+        assert_eq!(column(&lines[1], 'x'), column(&lines[2], 'Λ'));
+    }
+}